@@ -0,0 +1,149 @@
+/*
+*  Solves every board up to `MAX_SIDE` x `MAX_SIDE`, under standard and misère rules, when the crate is compiled and writes the
+*  results to `$OUT_DIR/tablebase.rs` for the `tablebase` module. Positions are stored in the
+*  canonical orientation chosen by `Position::canonical`, so the symmetries are shared with the crate.
+*/
+
+use std::collections::HashMap;
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+#[allow(dead_code)]
+#[path = "src/symmetry.rs"]
+mod symmetry;
+
+const MAX_SIDE: usize = 3;      // longest side solved; larger tables would bloat the WASM build
+
+
+struct Board {
+    // Geometry of one solved board, with pieces stored one bit per location (index)
+    width: usize,                   // number of columns on the board
+    height: usize,                  // number of rows on the board
+    lines: Vec<u16>,                // masks of every winning line
+    misere: bool,                   // True if completing a line loses instead of winning
+    symmetries: Vec<Vec<usize>>,    // location permutations that map the board onto itself
+}
+
+impl Board {
+    fn new(width: usize, height: usize, win_length: usize, misere: bool) -> Board {
+        // Lists the winning lines in every direction and the board's symmetries
+        let mut lines = vec![];
+        for &(row_step, col_step) in &[(0, 1), (1, 0), (1, 1), (1, -1)] {
+            for row in 0..height as isize {
+                for col in 0..width as isize {
+                    let cells: Vec<(isize, isize)> = (0..win_length as isize)
+                        .map(|step| (row + row_step * step, col + col_step * step))
+                        .collect();
+                    if cells.iter().all(|&(r, c)| r < height as isize && c >= 0 && c < width as isize) {
+                        lines.push(cells.iter().fold(0, |mask, &(r, c)| mask | 1 << (r as usize * width + c as usize)));
+                    }
+                }
+            }
+        }
+        Board { width, height, lines, misere, symmetries: symmetry::symmetry_mapping(width, height, 1) }
+    }
+
+    fn canonical(&self, pieces: (u16, u16)) -> (u16, u16) {
+        // Returns the smallest image of the pieces under the board's symmetries, comparing
+        // player 1's pieces first as `Position::canonical` does
+        let image = |bits: u16, symmetry: &Vec<usize>| -> u16 {
+            (0..self.width * self.height)
+                .filter(|&loc| bits >> loc & 1 == 1)
+                .fold(0, |mask, loc| mask | 1 << symmetry[loc])
+        };
+        self.symmetries.iter().map(|symmetry| (image(pieces.0, symmetry), image(pieces.1, symmetry))).min().unwrap()
+    }
+
+    fn is_won(&self, bits: u16) -> bool {
+        // Returns True if the pieces complete a line
+        self.lines.iter().any(|&line| line & !bits == 0)
+    }
+
+    fn open(&self, pieces: (u16, u16)) -> Vec<usize> {
+        // Returns the open locations (indices), or none once the game is over
+        if self.is_won(pieces.0) || self.is_won(pieces.1) {
+            return vec![];
+        }
+        (0..self.width * self.height).filter(|&loc| (pieces.0 | pieces.1) >> loc & 1 == 0).collect()
+    }
+
+    fn play(&self, pieces: (u16, u16), loc: usize) -> (u16, u16) {
+        // Places a piece for the player to move, who is player 1 when both have as many pieces
+        match pieces.0.count_ones() == pieces.1.count_ones() {
+            true => (pieces.0 | 1 << loc, pieces.1),
+            false => (pieces.0, pieces.1 | 1 << loc),
+        }
+    }
+
+    fn score(&self, pieces: (u16, u16), scores: &mut HashMap<(u16, u16), i8>) -> i8 {
+        // Scores the position for the player to move: positive for a win, larger the sooner it comes,
+        // negative for a loss, larger the later it comes, and zero for a draw
+        if let Some(&score) = scores.get(&pieces) {
+            return score;
+        }
+        let empty = (self.width * self.height) as i8 - (pieces.0 | pieces.1).count_ones() as i8;
+        // the player who just moved completed a line, winning or losing by the rules
+        let score = match self.is_won(pieces.0) || self.is_won(pieces.1) {
+            true if self.misere => empty + 1,
+            true => -(empty + 1),
+            false => self.open(pieces).into_iter()
+                .map(|loc| -self.score(self.play(pieces, loc), scores))
+                .max()
+                .unwrap_or(0),
+        };
+        scores.insert(pieces, score);
+        score
+    }
+}
+
+fn solve_board(board: &Board) -> Vec<(u32, u8, u16)> {
+    // Returns the key, value and best moves of every reachable position in canonical orientation,
+    // sorted by key. Values are 0 for a loss, 1 for a draw and 2 for a win.
+    let mut scores = HashMap::new();
+    let mut entries = HashMap::new();
+    let mut frontier = vec![(0, 0)];
+    while let Some(pieces) = frontier.pop() {
+        let canonical = board.canonical(pieces);
+        if entries.contains_key(&canonical) {
+            continue;
+        }
+        let moves: Vec<(usize, i8)> = board.open(canonical).into_iter()
+            .map(|loc| (loc, -board.score(board.play(canonical, loc), &mut scores)))
+            .collect();
+        let score = board.score(canonical, &mut scores);
+        let best = moves.iter().filter(|&&(_, child)| child == score).fold(0, |mask, &(loc, _)| mask | 1 << loc);
+        let value = (score.signum() + 1) as u8;
+        entries.insert(canonical, (value, best));
+        frontier.extend(moves.iter().map(|&(loc, _)| board.play(canonical, loc)));
+    }
+    let mut entries: Vec<(u32, u8, u16)> = entries.into_iter()
+        .map(|((first, second), (value, best))| (first as u32 | (second as u32) << 16, value, best))
+        .collect();
+    entries.sort();
+    entries
+}
+
+fn main() {
+    // Write one table per board shape, win length and rules up to `MAX_SIDE` x `MAX_SIDE`
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=src/symmetry.rs");
+    let mut source = String::from("// Generated by build.rs: (width, height, win_length, misere, [(key, value, best moves)])\n");
+    source += "static TABLES: &[Table] = &[\n";
+    for height in 1..=MAX_SIDE {
+        for width in 1..=MAX_SIDE {
+            for (win_length, misere) in (1..=width.max(height)).flat_map(|win_length| [(win_length, false), (win_length, true)]) {
+                let entries = solve_board(&Board::new(width, height, win_length, misere));
+                write!(source, "    ({}, {}, {}, {}, &[", width, height, win_length, misere).unwrap();
+                for (key, value, best) in entries {
+                    write!(source, "({}, {}, {}), ", key, value, best).unwrap();
+                }
+                source += "]),\n";
+            }
+        }
+    }
+    source += "];\n";
+    let out_dir = env::var("OUT_DIR").expect("cargo sets OUT_DIR for build scripts");
+    fs::write(Path::new(&out_dir).join("tablebase.rs"), source).expect("unable to write the tablebase");
+}
@@ -0,0 +1,207 @@
+/*
+*  Automatic players that search the game tree instead of moving at random.
+*/
+
+use crate::position::Position;
+use crate::{Game, Rules};
+
+const WIN_SCORE: i32 = 1000;    // base score for a won position, adjusted by depth to prefer quick wins
+const MINIMAX_NODES: u64 = 1_000_000;   // most move sequences a minimax search may walk
+
+
+struct Minimax {
+    // Search state shared by every node of one minimax search
+    rules: Rules,               // whether completing a line wins or loses
+    max_depth: Option<usize>,   // plies to look ahead, `None` to search to the end of the game
+}
+
+impl Minimax {
+    fn new(game: &Game, max_depth: Option<usize>) -> Self {
+        // Set up a search under the game's rules; the board itself comes from `Game::position`
+        Minimax { rules: game.rules, max_depth }
+    }
+
+    fn negamax(&self, position: Position, last: usize, depth: i32) -> i32 {
+        // Scores the position from the point of view of the player to move, after the other player played on `last`
+        if position.completes_line(last) {
+            return match self.rules {
+                Rules::Standard => -(WIN_SCORE - depth),
+                Rules::Misere => WIN_SCORE - depth,
+            };
+        }
+        // positions beyond the search horizon are scored as draws
+        if self.max_depth.is_some_and(|max_depth| depth as usize >= max_depth) {
+            return 0;
+        }
+
+        // no open squares left means the game is drawn
+        position.playable_moves().into_iter()
+            .map(|loc| -self.score_move(position, loc, depth + 1))
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn score_move(&self, position: Position, loc: usize, depth: i32) -> i32 {
+        // Plays the player to move on `loc` and scores the reply from the other player's point of view
+        self.negamax(position.with_move(loc), loc, depth)
+    }
+}
+
+pub fn minimax_move(game: &Game, max_depth: Option<usize>) -> Option<usize> {
+    // Returns the location (index) with the best minimax value for the current player, looking at most
+    // `max_depth` plies ahead (None for the whole game), or None if the game is over.
+    // Ties go to the lowest index so play is reproducible.
+    best_moves(game, max_depth).into_iter()
+        .fold(None, |best: Option<(usize, i32)>, (loc, score)| match best {
+            Some((_, best_score)) if best_score >= score => best,
+            _ => Some((loc, score)),
+        })
+        .map(|(loc, _)| loc)
+}
+
+pub fn minimax_value(game: &Game) -> i32 {
+    // Returns the minimax value of the position for the current player:
+    // positive if they can force a win, zero for a draw and negative for a forced loss
+    if let Some(player) = game.winner() {
+        return if player == game.curr_player { WIN_SCORE } else { -WIN_SCORE };
+    }
+    best_moves(game, None).into_iter().map(|(_, score)| score).max().unwrap_or(0)
+}
+
+pub fn supports(game: &Game, max_depth: Option<usize>) -> bool {
+    // Returns True if a minimax search `max_depth` plies deep (None for the whole game) walks at most
+    // `MINIMAX_NODES` move sequences from this position, counting every open square as a move
+    // and, under gravity, every open column at every ply
+    let moves = game.legal_moves().len() as u64;
+    let open = game.total_squares() - (game.pieces[0] | game.pieces[1]).count();
+    let depth = max_depth.map_or(open, |max_depth| max_depth.min(open)) as u64;
+    (0..depth)
+        .try_fold(1u64, |sequences, ply| {
+            sequences.checked_mul(if game.gravity { moves } else { moves.saturating_sub(ply) })
+        })
+        .is_some_and(|sequences| sequences <= MINIMAX_NODES)
+}
+
+fn best_moves(game: &Game, max_depth: Option<usize>) -> Vec<(usize, i32)> {
+    // Scores every legal move for the current player
+    if game.end_game {
+        return vec![];
+    }
+    let search = Minimax::new(game, max_depth);
+    let position = game.position();
+    game.legal_moves().into_iter()
+        .map(|loc| (loc, -search.score_move(position, loc, 1)))
+        .collect()
+}
+
+
+/***********
+ UNIT TESTS
+************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::{AiPlayer, Player};
+    use crate::{Difficulty, Outcome, Strategy};
+
+    fn play(game: &mut Game, moves: &[usize]) {
+        // Plays the given locations in order
+        for &loc in moves {
+            game.place(loc).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_minimax_takes_win() {
+        // X to move with two in a row should complete the line rather than block
+        let mut game = Game::new();
+        play(&mut game, &[0, 3, 1, 4]);
+        assert_eq!(minimax_move(&game, None), Some(2));
+    }
+
+    #[test]
+    fn test_minimax_blocks_loss() {
+        // O must block X's top row
+        let mut game = Game::new();
+        play(&mut game, &[0, 4, 1]);
+        assert_eq!(minimax_move(&game, None), Some(2));
+    }
+
+    #[test]
+    fn test_minimax_misere() {
+        // Under misère rules X avoids completing its top row, and perfect play still draws
+        let mut game = Game::new();
+        game.set_rules(Rules::Misere);
+        assert_eq!(minimax_value(&game), 0);
+        play(&mut game, &[0, 3, 1, 4]);
+        assert_ne!(minimax_move(&game, None), Some(2));
+    }
+
+    #[test]
+    fn test_minimax_value_empty_board() {
+        // Perfect play from the empty board is a draw
+        let game = Game::new();
+        assert_eq!(minimax_value(&game), 0);
+    }
+
+    #[test]
+    fn test_minimax_game_over() {
+        // No move is offered once the game has ended
+        let mut game = Game::new();
+        play(&mut game, &[0, 3, 1, 4, 2]);
+        assert_eq!(minimax_move(&game, None), None);
+        assert_eq!(minimax_value(&game), -WIN_SCORE);
+    }
+
+    #[test]
+    fn test_minimax_depth_limit() {
+        // Looking one move ahead misses X's threat on the middle row, while two moves ahead blocks it
+        let mut game = Game::new();
+        play(&mut game, &[4, 0, 5]);
+        assert_eq!(minimax_move(&game, Some(1)), Some(1));
+        assert_eq!(minimax_move(&game, Some(2)), Some(3));
+    }
+
+    #[test]
+    fn test_supports() {
+        // Whole games fit the budget only on small boards, and shallow searches fit on large ones
+        assert!(supports(&Game::new(), None));
+        assert!(!supports(&Game::with_size(4, 4, 4), None));
+        assert!(supports(&Game::with_size(15, 15, 5), Some(2)));
+        assert!(!supports(&Game::with_size(15, 15, 5), Some(4)));
+        let mut connect_four = Game::with_size(7, 6, 4);
+        connect_four.set_gravity(true);
+        assert!(supports(&connect_four, Some(4)));
+        assert!(!supports(&connect_four, None));
+    }
+
+    #[test]
+    fn test_minimax_self_play_draws() {
+        // Two perfect players always draw the standard game
+        let mut game = Game::new();
+        let mut players = [AiPlayer::new(Strategy::Minimax, Difficulty::Perfect); 2];
+        while !game.end_game {
+            let loc = players[game.curr_player].choose_move(&game).unwrap();
+            game.place(loc).unwrap();
+        }
+        assert_eq!(game.outcome, Outcome::Draw);
+    }
+
+    #[test]
+    fn test_minimax_never_loses_to_random() {
+        // The perfect player never loses as either player against random moves
+        for minimax_player in 0..2 {
+            for _round in 0..5 {
+                let mut game = Game::new();
+                let mut players = [AiPlayer::new(Strategy::Random, Difficulty::Random); 2];
+                players[minimax_player] = AiPlayer::new(Strategy::Minimax, Difficulty::Perfect);
+
+                while !game.end_game {
+                    let loc = players[game.curr_player].choose_move(&game).unwrap();
+                    game.place(loc).unwrap();
+                }
+                assert_ne!(game.winner(), Some(1 - minimax_player));
+            }
+        }
+    }
+}
@@ -0,0 +1,203 @@
+/*
+*  Fixed-size set of board locations stored one bit per location (index). Boards are checked for
+*  wins and draws with a few mask operations, without allocating.
+*/
+
+use std::ops::{BitAnd, BitOr, Not, Shr};
+
+pub const MAX_SQUARES: usize = 256;         // largest board a bitboard can hold
+const WORDS: usize = MAX_SQUARES / 64;      // 64-bit words in a bitboard
+
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Bitboard([u64; WORDS]);          // bit `loc % 64` of word `loc / 64` is set for each location in the set
+
+impl Bitboard {
+    pub const EMPTY: Bitboard = Bitboard([0; WORDS]);
+
+    pub fn single(loc: usize) -> Bitboard {
+        // The set holding only the location (index)
+        Bitboard::EMPTY.with(loc)
+    }
+
+    pub fn first(count: usize) -> Bitboard {
+        // The set of locations `0..count`, e.g. every square of a board with `count` squares
+        let mut board = Bitboard::EMPTY;
+        for (word, bits) in board.0.iter_mut().enumerate() {
+            let start = word * 64;
+            *bits = match count.saturating_sub(start) {
+                0 => 0,
+                filled if filled >= 64 => u64::MAX,
+                filled => (1 << filled) - 1,
+            };
+        }
+        board
+    }
+
+    pub fn from_locs(locs: &[usize]) -> Bitboard {
+        // The set of the given locations (indices)
+        locs.iter().fold(Bitboard::EMPTY, |board, &loc| board.with(loc))
+    }
+
+    pub fn contains(&self, loc: usize) -> bool {
+        // Returns True if the location (index) is in the set
+        self.0[loc / 64] >> (loc % 64) & 1 == 1
+    }
+
+    pub fn contains_all(&self, other: Bitboard) -> bool {
+        // Returns True if every location in `other` is also in this set
+        *self & other == other
+    }
+
+    pub fn with(mut self, loc: usize) -> Bitboard {
+        // Returns the set with the location (index) added
+        self.0[loc / 64] |= 1 << (loc % 64);
+        self
+    }
+
+    pub fn without(mut self, loc: usize) -> Bitboard {
+        // Returns the set with the location (index) removed
+        self.0[loc / 64] &= !(1 << (loc % 64));
+        self
+    }
+
+    pub fn count(&self) -> usize {
+        // Returns the number of locations in the set
+        self.0.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        // Returns True if the set has no locations
+        *self == Bitboard::EMPTY
+    }
+
+    pub fn lowest(&self) -> Option<usize> {
+        // Returns the smallest location (index) in the set
+        self.0.iter().enumerate()
+            .find(|(_, &word)| word != 0)
+            .map(|(word, bits)| word * 64 + bits.trailing_zeros() as usize)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        // Returns the locations (indices) in the set in increasing order, stepping from one set bit
+        // of each word to the next rather than testing every location
+        self.0.iter().enumerate().flat_map(|(word, &bits)| {
+            let mut bits = bits;
+            std::iter::from_fn(move || {
+                if bits == 0 {
+                    return None;
+                }
+                let loc = word * 64 + bits.trailing_zeros() as usize;
+                bits &= bits - 1;
+                Some(loc)
+            })
+        })
+    }
+}
+
+impl BitAnd for Bitboard {
+    type Output = Bitboard;
+
+    fn bitand(self, other: Bitboard) -> Bitboard {
+        // Locations in both sets
+        let mut board = self;
+        for word in 0..WORDS {
+            board.0[word] &= other.0[word];
+        }
+        board
+    }
+}
+
+impl BitOr for Bitboard {
+    type Output = Bitboard;
+
+    fn bitor(self, other: Bitboard) -> Bitboard {
+        // Locations in either set
+        let mut board = self;
+        for word in 0..WORDS {
+            board.0[word] |= other.0[word];
+        }
+        board
+    }
+}
+
+impl Not for Bitboard {
+    type Output = Bitboard;
+
+    fn not(self) -> Bitboard {
+        // Locations missing from the set, up to `MAX_SQUARES`
+        let mut board = self;
+        for word in 0..WORDS {
+            board.0[word] = !board.0[word];
+        }
+        board
+    }
+}
+
+impl Shr<usize> for Bitboard {
+    type Output = Bitboard;
+
+    fn shr(self, shift: usize) -> Bitboard {
+        // Moves every location down by `shift`, so location `loc + shift` lands on `loc`
+        let mut board = Bitboard::EMPTY;
+        let (words, bits) = (shift / 64, shift % 64);
+        for word in 0..WORDS.saturating_sub(words) {
+            let low = self.0[word + words] >> bits;
+            let high = match (bits, self.0.get(word + words + 1)) {
+                (0, _) | (_, None) => 0,
+                (_, Some(&next)) => next << (64 - bits),
+            };
+            board.0[word] = low | high;
+        }
+        board
+    }
+}
+
+
+/***********
+ UNIT TESTS
+************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_operations() {
+        // Locations can be added, removed, counted and combined
+        let board = Bitboard::from_locs(&[0, 63, 64, 255]);
+        assert!(board.contains(63) && board.contains(64) && board.contains(255));
+        assert!(!board.contains(1));
+        assert_eq!(board.count(), 4);
+        assert_eq!(board.without(0).lowest(), Some(63));
+        assert_eq!((board & Bitboard::single(64)).iter().collect::<Vec<usize>>(), vec![64]);
+        assert!(board.contains_all(Bitboard::from_locs(&[0, 255])));
+        assert!(!board.contains_all(Bitboard::from_locs(&[0, 1])));
+        assert_eq!((board | Bitboard::single(1)).count(), 5);
+    }
+
+    #[test]
+    fn test_iter_in_order() {
+        // Iteration lists the set locations in increasing order across every word
+        let locs = vec![0, 1, 63, 64, 127, 130, 255];
+        assert_eq!(Bitboard::from_locs(&locs).iter().collect::<Vec<usize>>(), locs);
+        assert_eq!(Bitboard::EMPTY.iter().next(), None);
+    }
+
+    #[test]
+    fn test_first() {
+        // The first `count` locations fill whole words and part of the next
+        assert_eq!(Bitboard::first(9).count(), 9);
+        assert_eq!(Bitboard::first(70).count(), 70);
+        assert!(Bitboard::first(70).contains(69) && !Bitboard::first(70).contains(70));
+        assert_eq!(Bitboard::first(MAX_SQUARES), !Bitboard::EMPTY);
+    }
+
+    #[test]
+    fn test_shift_crosses_words() {
+        // Shifting moves locations across word boundaries and drops those below zero
+        let board = Bitboard::from_locs(&[3, 64, 200]);
+        assert_eq!(board >> 4, Bitboard::from_locs(&[60, 196]));
+        assert_eq!(board >> 128, Bitboard::from_locs(&[72]));
+        assert_eq!(board >> 0, board);
+    }
+}
@@ -0,0 +1,117 @@
+/*
+*  Move hints: every legal square of the current position scored as a win, draw or loss for the player
+*  to move, with the number of moves until that result, so front ends can coach players square by square.
+*/
+
+use std::fmt;
+use crate::search::{AlphaBeta, SearchLimits};
+use crate::solve::Value;
+use crate::{Game, SEARCH_NODES};
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Evaluation {
+    // What perfect play makes of one move for the player to move
+    pub loc: usize,             // location (index) of the move
+    pub value: Option<Value>,   // result for the player making the move, None if the search could not settle it
+    pub plies: Option<usize>,   // moves until that result, counting this one, None when the value is unknown
+}
+
+impl fmt::Display for Evaluation {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        // Describe the result and how soon it comes, e.g. "win in 3"
+        match (self.value, self.plies) {
+            (Some(Value::Draw), _) => write!(formatter, "draw"),
+            (Some(value), Some(plies)) => write!(formatter, "{} in {}", value, plies),
+            _ => write!(formatter, "unknown"),
+        }
+    }
+}
+
+pub fn evaluate(game: &Game) -> Option<Vec<Evaluation>> {
+    // Scores every legal move in location (index) order, sharing one alpha-beta player's budget between them,
+    // or returns None for a wild game, whose lines the search cannot credit
+    let open = game.legal_moves().len();
+    let limits = SearchLimits::nodes(SEARCH_NODES / open.max(1) as u64);
+    evaluate_with(game, limits)
+}
+
+pub fn evaluate_with(game: &Game, limits: SearchLimits) -> Option<Vec<Evaluation>> {
+    // Scores every legal move in location (index) order, searching each within `limits`, or returns None
+    // for a wild game. A drawn game always runs until the board is full, so a draw is as far away as the open squares
+    if game.wild {
+        return None;
+    }
+    let open = game.legal_moves().len();
+    let evaluations = AlphaBeta::new(game).evaluate_moves(game, limits).into_iter()
+        .map(|result| {
+            let loc = result.best_move.expect("every evaluated move is recorded");
+            match (result.is_win(), result.is_draw(), result.is_loss()) {
+                (true, _, _) => Evaluation { loc, value: Some(Value::Win), plies: result.distance() },
+                (_, true, _) => Evaluation { loc, value: Some(Value::Draw), plies: Some(open) },
+                (_, _, true) => Evaluation { loc, value: Some(Value::Loss), plies: result.distance() },
+                _ => Evaluation { loc, value: None, plies: None },
+            }
+        })
+        .collect();
+    Some(evaluations)
+}
+
+
+/***********
+ UNIT TESTS
+************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solve::solve;
+
+    #[test]
+    fn test_matches_solver() {
+        // On the standard board every hint is exact and agrees with the exhaustive solver
+        let mut game = Game::new();
+        for &loc in &[0, 1] {
+            game.place(loc).unwrap();
+        }
+        let evaluations = evaluate(&game).unwrap();
+        let solution = solve(&game.position());
+        assert_eq!(evaluations.len(), solution.moves.len());
+        for (evaluation, summary) in evaluations.iter().zip(&solution.moves) {
+            assert_eq!((evaluation.loc, evaluation.value), (summary.loc, Some(summary.value)));
+        }
+        assert_eq!(evaluate(&Game::new()).unwrap().iter().map(|evaluation| evaluation.plies).collect::<Vec<Option<usize>>>(),
+                   vec![Some(9); 9]);
+    }
+
+    #[test]
+    fn test_distance_to_result() {
+        // Winning now is one move away, and ignoring the opponent's threat loses on their next move
+        let mut game = Game::new();
+        for &loc in &[0, 3, 1, 4] {
+            game.place(loc).unwrap();
+        }
+        let evaluations = evaluate(&game).unwrap();
+        assert_eq!(evaluations[0].to_string(), "win in 1");
+        assert_eq!(evaluations[1].to_string(), "draw");
+        assert_eq!(evaluations[4], Evaluation { loc: 8, value: Some(Value::Loss), plies: Some(2) });
+        game.place(2).unwrap();
+        assert_eq!(evaluate(&game), Some(vec![]));
+    }
+
+    #[test]
+    fn test_unknown_when_out_of_budget() {
+        // A search too small to settle a large board reports the value as unknown
+        let game = Game::with_size(7, 7, 4);
+        let evaluations = evaluate_with(&game, SearchLimits::nodes(1)).unwrap();
+        assert_eq!(evaluations.len(), 49);
+        assert_eq!(evaluations[24].to_string(), "unknown");
+    }
+
+    #[test]
+    fn test_no_hints_in_wild_games() {
+        // The search credits lines to the owner of their pieces, so it cannot score a wild game
+        let mut game = Game::new();
+        game.set_wild(true);
+        assert_eq!(evaluate(&game), None);
+    }
+}
@@ -0,0 +1,1569 @@
+/*
+*  Rules engine shared by the command-line (`tic-tac-toe`) and browser (`wasm-tic-tac-toe`) games.
+*  Front ends own input and rendering, while this crate owns the board, turn order and endgame rules.
+*/
+
+pub mod ai;
+pub mod bitboard;
+pub mod hint;
+pub mod mcts;
+pub mod notakto;
+pub mod player;
+pub mod position;
+pub mod renju;
+pub mod rng;
+pub mod search;
+pub mod solve;
+pub mod symmetry;
+pub mod tablebase;
+pub mod ultimate;
+mod timer;
+
+use std::error::Error;
+use std::fmt;
+use bitboard::{Bitboard, MAX_SQUARES};
+use position::Position;
+use rng::SeededRng;
+
+pub const P1: char = 'X';           // player 1's piece
+pub const P2: char = 'O';           // player 2's piece
+pub const SIZE: usize = 3;          // default row/col sizes and win length for board
+pub const SEARCH_NODES: u64 = 250_000;  // positions an alpha-beta player may search per move
+const DIRECTIONS: [(isize, isize, isize); 13] = [   // layer, row and column steps of every line direction
+    (0, 0, 1), (0, 1, 0), (0, 1, 1), (0, 1, -1),    // row, column, diagonal and anti-diagonal within a layer
+    (1, 0, 0), (1, 0, 1), (1, 0, -1), (1, 1, 0), (1, -1, 0),    // straight down and diagonally across the layers
+    (1, 1, 1), (1, 1, -1), (1, -1, 1), (1, -1, -1),             // corner to corner through every layer
+];
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    // How an automatic player chooses its moves
+    Random,     // uniformly random legal moves
+    Minimax,    // perfect play found by searching the full game tree
+    AlphaBeta,  // pruned, budgeted search that scales to larger boards; depth follows the difficulty
+    Mcts,       // Monte Carlo tree search guided by random playouts
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Difficulty {
+    // How strongly an automatic player plays: how far it searches and how often it blunders
+    Random,     // every move is random
+    Easy,       // looks one move ahead and blunders often
+    Medium,     // looks two moves ahead and blunders now and then
+    Hard,       // looks four moves ahead and rarely blunders
+    Perfect,    // searches as far as its strategy allows and never blunders
+}
+
+impl Difficulty {
+    pub fn search_depth(self) -> Option<usize> {
+        // Returns the deepest an alpha-beta or minimax search may look, in plies, or None for no limit
+        match self {
+            Difficulty::Random => Some(0),
+            Difficulty::Easy => Some(1),
+            Difficulty::Medium => Some(2),
+            Difficulty::Hard => Some(4),
+            Difficulty::Perfect => None,
+        }
+    }
+
+    pub fn playouts(self) -> u64 {
+        // Returns how many playouts a Monte Carlo tree search may run per move
+        match self {
+            Difficulty::Random => 0,
+            Difficulty::Easy => 100,
+            Difficulty::Medium => 1_000,
+            Difficulty::Hard => 4_000,
+            Difficulty::Perfect => mcts::MCTS_ITERATIONS,
+        }
+    }
+
+    pub fn mistake_rate(self) -> f64 {
+        // Returns the probability of playing a random move instead of the searched one
+        match self {
+            Difficulty::Random => 1.0,
+            Difficulty::Easy => 0.3,
+            Difficulty::Medium => 0.15,
+            Difficulty::Hard => 0.05,
+            Difficulty::Perfect => 0.0,
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        // Name the tier for players
+        let name = match self {
+            Difficulty::Random => "random",
+            Difficulty::Easy => "easy",
+            Difficulty::Medium => "medium",
+            Difficulty::Hard => "hard",
+            Difficulty::Perfect => "perfect",
+        };
+        write!(formatter, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Rules {
+    // What completing a line of `win_length` pieces means
+    #[default]
+    Standard,   // the player who completes a line wins
+    Misere,     // the player who completes a line loses, so the opponent wins
+}
+
+impl Rules {
+    pub fn line_winner(self, player: usize) -> usize {
+        // Returns the player who wins when `player` completes a line
+        match self {
+            Rules::Standard => player,
+            Rules::Misere => 1 - player,
+        }
+    }
+}
+
+impl fmt::Display for Rules {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        // Name the rules for players
+        let name = match self {
+            Rules::Standard => "standard",
+            Rules::Misere => "misère",
+        };
+        write!(formatter, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Restriction {
+    // Which lines count and where the first player may move, for gomoku-style games
+    #[default]
+    Freestyle,  // any `win_length` pieces in a row complete a line, even as part of a longer run
+    Exact,      // only exactly `win_length` pieces in a row complete a line; overlines do not count
+    Renju,      // as `Exact` for player 1, who may not make an overline, double four or double three;
+                // player 2's overlines still count
+}
+
+impl Restriction {
+    pub fn exact(self, player: usize) -> bool {
+        // Returns True if a run longer than `win_length` does not complete a line for `player`
+        match self {
+            Restriction::Freestyle => false,
+            Restriction::Exact => true,
+            Restriction::Renju => player == 0,
+        }
+    }
+
+    pub fn fouls(self, player: usize) -> bool {
+        // Returns True if `player` may not make the moves that renju counts as fouls
+        self == Restriction::Renju && player == 0
+    }
+}
+
+impl fmt::Display for Restriction {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        // Name the restriction for players
+        let name = match self {
+            Restriction::Freestyle => "freestyle",
+            Restriction::Exact => "exact",
+            Restriction::Renju => "renju",
+        };
+        write!(formatter, "{}", name)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Coord {
+    // Struct for mapping array indices to coordinates
+    pub x: usize,       // x-coordinate: row of `board`, counting the rows of every layer above
+    pub y: usize,       // y-coordinate: column of `board`
+    pub z: usize,       // z-coordinate: layer of the board, 0 on a flat board
+    pub legal: bool,    // flag: True if it is legal to place a piece on the coordinate, False if coordinate is already full
+                        // or, under gravity, is not the lowest open square of its column
+}
+
+#[derive(Debug, PartialEq)]
+pub struct WinState {
+    // Struct containing representations of win states
+    pub p1_win_state: Vec<char>,    // represents player 1's win state
+    pub p2_win_state: Vec<char>,    // represents player 2's win state
+}
+
+impl WinState {
+    pub fn new(win_length: usize) -> WinState {
+        // Generates winning state sized to `win_length` pieces in a row
+        WinState { p1_win_state: vec![P1; win_length], p2_win_state: vec![P2; win_length] }
+    }
+}
+
+impl Default for WinState {
+    fn default() -> WinState {
+        // Generates winning state sized to default `SIZE`
+        WinState::new(SIZE)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    // State of play after a move
+    InProgress,                                 // no win yet and free squares remain
+    Win { player: usize, line: Vec<usize> },    // `player` won when the locations (indices) in `line` were completed
+    Draw,                                       // the board filled up without a win
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Move {
+    // A piece placed during the game
+    pub player: usize,  // player who placed the piece
+    pub loc: usize,     // location (index) the piece was placed on
+    pub symbol: usize,  // piece placed, as an index into `players`: the player's own except in wild games
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveError {
+    // Reasons a move is rejected by the engine
+    OutOfRange(usize),      // location (index) is not on the board
+    Occupied(usize),        // a piece is already placed on the location
+    GameOver,               // the game has already been won or drawn
+    NotYourTurn(usize),     // the player who tried to move is not the current player
+    WrongBoard(usize),      // in ultimate tic-tac-toe, the small board cannot be played on this turn
+    Forbidden(usize),       // under renju rules, the move is a foul for player 1
+    Unsupported(usize),     // under gravity, the location (index) has an open square below it
+    ColumnFull(usize),      // under gravity, the column has no open square left, or is not on the board
+    WrongPiece(usize),      // outside wild games, the piece (index into `players`) is not the mover's own
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        // Describe the rejected move for players
+        match self {
+            MoveError::OutOfRange(loc) => write!(formatter, "location {} is not on the board", loc),
+            MoveError::Occupied(loc) => write!(formatter, "a piece is already placed on location {}", loc),
+            MoveError::GameOver => write!(formatter, "the game is already over"),
+            MoveError::NotYourTurn(player) => write!(formatter, "it is not player {}'s turn", player + 1),
+            MoveError::WrongBoard(board) => write!(formatter, "board {} cannot be played on this turn", board + 1),
+            MoveError::Forbidden(loc) => write!(formatter, "location {} is a foul under renju rules", loc),
+            MoveError::Unsupported(loc) => write!(formatter, "location {} has an open square below it", loc),
+            MoveError::ColumnFull(column) => write!(formatter, "column {} has no open square", column + 1),
+            MoveError::WrongPiece(symbol) => write!(formatter, "player {}'s piece cannot be placed on this turn", symbol + 1),
+        }
+    }
+}
+
+impl Error for MoveError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoardError {
+    // Reasons a board size is rejected by the engine
+    Empty,                  // the board has no rows, columns or layers
+    TooLarge(usize),        // the board has more than `MAX_SQUARES` squares, holding its number of squares
+    WinLength { win_length: usize, width: usize, height: usize, layers: usize },  // no room for the win length
+}
+
+impl fmt::Display for BoardError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        // Describe the rejected board for players
+        match *self {
+            BoardError::Empty => write!(formatter, "board must have at least one row, column and layer"),
+            BoardError::TooLarge(squares) => write!(formatter, "board must have at most {} squares, not {}", MAX_SQUARES, squares),
+            BoardError::WinLength { win_length, width, height, layers } =>
+                write!(formatter, "win length {} does not fit on a {} board", win_length, board_name(width, height, layers)),
+        }
+    }
+}
+
+impl Error for BoardError {}
+
+#[derive(Debug, PartialEq)]
+pub struct Game {
+    // Struct with tic-tac-toe game settings and components
+    pub board: Vec<Vec<char>>,          // tic tac toe board, indexed as board[row][column], each layer's rows after the last
+    pub width: usize,                   // number of columns on the board
+    pub height: usize,                  // number of rows in each layer of the board
+    pub layers: usize,                  // number of layers stacked into the board, 1 for a flat board
+    pub win_length: usize,              // number of pieces in a row needed to win
+    pub rules: Rules,                   // whether completing a line wins or loses
+    pub restriction: Restriction,       // whether overlines count and which moves are fouls for player 1
+    pub gravity: bool,                  // True if pieces drop to the lowest open square of their column
+    pub wild: bool,                     // True if each player may place either piece, winning with any line they complete
+    pub lines: Vec<Vec<usize>>,         // every run of `win_length` locations that can win the game
+    pub lines_through: Vec<Vec<usize>>, // indices into `lines` of the lines through each location
+    pub pieces: [Bitboard; 2],          // each player's pieces as a bitmask, kept in step with `board`
+    pub curr_player: usize,             // current player
+    pub players: [char; 2],             // players represented by pieces
+    pub end_game: bool,                 // game status: False if in play, True if ended by win/draw
+    pub coordinates: Vec<Coord>,        // coordinates for moves
+    pub win_states: WinState,           // win states for players
+    pub outcome: Outcome,               // whether the game is in play, won (and by whom) or drawn
+    pub rng: SeededRng,                 // source of every random move, seeded so games can be replayed
+    pub history: Vec<Move>,             // moves played so far, in order
+    pub undone: Vec<Move>,              // moves taken back by `undo`, most recent last, replayed by `redo`
+}
+
+impl Default for Game {
+    fn default() -> Game {
+        Game::new()
+    }
+}
+
+impl Game {
+    pub fn new() -> Self {
+        // Initializes the standard 3x3 game with three in a row to win
+        Game::with_size(SIZE, SIZE, SIZE)
+    }
+
+    pub fn with_size(width: usize, height: usize, win_length: usize) -> Self {
+        // Initializes a `width` x `height` game board won by `win_length` pieces in a row
+        // and the first player piece
+        Game::with_layers(width, height, 1, win_length)
+    }
+
+    pub fn with_layers(width: usize, height: usize, layers: usize, win_length: usize) -> Self {
+        // Initializes a three-dimensional game of `layers` stacked `width` x `height` boards, won by
+        // `win_length` pieces in a row within a layer or through the layers, e.g. 4x4x4 Qubic,
+        // panicking if the board cannot be played, see `try_with_layers`
+        Game::try_with_layers(width, height, layers, win_length).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    pub fn try_with_layers(width: usize, height: usize, layers: usize, win_length: usize) -> Result<Self, BoardError> {
+        // As `with_layers`, but returns why the board cannot be played: it has no squares, more squares
+        // than `MAX_SQUARES` or no room for `win_length` in a row
+        if width == 0 || height == 0 || layers == 0 {
+            return Err(BoardError::Empty);
+        }
+        let squares = width.saturating_mul(height).saturating_mul(layers);
+        if squares > MAX_SQUARES {
+            return Err(BoardError::TooLarge(squares));
+        }
+        if win_length == 0 || win_length > width.max(height).max(layers) {
+            return Err(BoardError::WinLength { win_length, width, height, layers });
+        }
+        let lines = line_mapping(width, height, layers, win_length);
+        Ok(Self {
+            board: vec![vec![' '; width]; height * layers],
+            width,
+            height,
+            layers,
+            win_length,
+            rules: Rules::Standard,
+            restriction: Restriction::Freestyle,
+            gravity: false,
+            wild: false,
+            lines_through: lines_through(width * height * layers, &lines),
+            lines,
+            pieces: [Bitboard::EMPTY; 2],
+            curr_player: 0,
+            players: [P1, P2],
+            end_game: false,
+            coordinates: coord_mapping(width, height, layers),
+            win_states: WinState::new(win_length),
+            outcome: Outcome::InProgress,
+            rng: SeededRng::from_entropy(),
+            history: vec![],
+            undone: vec![],
+        })
+    }
+
+    pub fn total_squares(&self) -> usize {
+        // Returns the number of locations (indices) on the board
+        self.width * self.height * self.layers
+    }
+
+    pub fn location(&self, layer: usize, row: usize, col: usize) -> Option<usize> {
+        // Returns the location (index) of the square in `row` and `col` of `layer`, each counted from 0,
+        // or None if the square is not on the board
+        match layer < self.layers && row < self.height && col < self.width {
+            true => Some((layer * self.height + row) * self.width + col),
+            false => None,
+        }
+    }
+
+    pub fn notation(&self, loc: usize) -> Option<String> {
+        // Names the location (index) by column letter and row number, counting rows from the bottom
+        // as gomoku records do, e.g. "h8" for the centre of a 15x15 board; None on layered boards
+        // or boards wider than the alphabet
+        if !self.has_notation() || loc >= self.total_squares() {
+            return None;
+        }
+        let column = (b'a' + (loc % self.width) as u8) as char;
+        Some(format!("{}{}", column, self.height - loc / self.width))
+    }
+
+    pub fn parse_notation(&self, square: &str) -> Option<usize> {
+        // Returns the location (index) named like "h8" by `notation`, ignoring case,
+        // or None if the name is not a square on the board
+        let square = square.trim().to_ascii_lowercase();
+        let mut chars = square.chars();
+        let column = chars.next().filter(|column| column.is_ascii_lowercase())?;
+        let row: usize = chars.as_str().parse().ok()?;
+        if !self.has_notation() || row == 0 || row > self.height {
+            return None;
+        }
+        self.location(0, self.height - row, (column as u8 - b'a') as usize)
+    }
+
+    pub fn has_notation(&self) -> bool {
+        // Returns True if every square can be named by column letter and row number
+        self.layers == 1 && self.width <= 26
+    }
+
+    pub fn set_seed(&mut self, seed: u64) {
+        // Restart the random number generator from `seed` so random moves can be replayed
+        self.rng = SeededRng::new(seed);
+    }
+
+    pub fn seed(&self) -> u64 {
+        // Returns the seed of the random number generator
+        self.rng.seed()
+    }
+
+    pub fn set_rules(&mut self, rules: Rules) {
+        // Choose whether completing a line wins or loses, rescoring any pieces already on the board
+        self.rules = rules;
+        self.end_game = self.is_endgame();
+    }
+
+    pub fn set_restriction(&mut self, restriction: Restriction) {
+        // Choose whether overlines count and whether renju fouls are forbidden,
+        // rescoring any pieces already on the board
+        self.restriction = restriction;
+        self.end_game = self.is_endgame();
+    }
+
+    pub fn set_gravity(&mut self, gravity: bool) {
+        // Choose whether pieces drop to the lowest open square of their column, as in Connect Four,
+        // so only that square of each column is legal
+        self.gravity = gravity;
+        self.refresh_legal();
+    }
+
+    pub fn set_wild(&mut self, wild: bool) {
+        // Choose whether each player may place either piece, as in wild tic-tac-toe,
+        // so a completed line is credited to the player who completed it rather than to the piece's owner
+        self.wild = wild;
+        self.end_game = self.is_endgame();
+    }
+
+    pub fn columns(&self) -> usize {
+        // Returns the number of columns pieces can be dropped into, counting each layer's columns in turn
+        self.width * self.layers
+    }
+
+    pub fn drop_location(&self, column: usize) -> Option<usize> {
+        // Returns the lowest open location (index) of the column, counted from 0 left to right and then
+        // layer by layer, or None if the column is full or not on the board
+        if column >= self.columns() {
+            return None;
+        }
+        let filled = self.pieces[0] | self.pieces[1];
+        (0..self.height).rev()
+            .filter_map(|row| self.location(column / self.width, row, column % self.width))
+            .find(|&loc| !filled.contains(loc))
+    }
+
+    pub fn drop(&mut self, column: usize) -> Result<Outcome, MoveError> {
+        // Drop the current player's piece into the column, see `drop_location`, and hand the turn over
+        match self.drop_location(column) {
+            Some(loc) => self.place(loc),
+            None => Err(MoveError::ColumnFull(column)),
+        }
+    }
+
+    fn refresh_legal(&mut self) {
+        // Marks the open squares as legal, or under gravity only the open squares resting on a piece or the bottom row
+        let position = self.position();
+        let legal: Vec<bool> = (0..self.total_squares())
+            .map(|loc| position.piece_at(loc).is_none() && position.is_supported(loc))
+            .collect();
+        for (coord, legal) in self.coordinates.iter_mut().zip(legal) {
+            coord.legal = legal;
+        }
+    }
+
+    pub fn place(&mut self, loc: usize) -> Result<Outcome, MoveError> {
+        // Place the current player's piece on the location (index) and hand the turn over
+        self.make_move(self.curr_player, loc)
+    }
+
+    pub fn place_symbol(&mut self, loc: usize, symbol: usize) -> Result<Outcome, MoveError> {
+        // Place the piece (index into `players`) on the location (index) for the current player,
+        // which must be their own piece unless the game is wild, and hand the turn over
+        self.play(Move { player: self.curr_player, loc, symbol })
+    }
+
+    pub fn make_move(&mut self, player: usize, loc: usize) -> Result<Outcome, MoveError> {
+        // Place `player`'s own piece on the location (index) if the move is legal,
+        // returning the outcome of the game after the move
+        self.play(Move { player, loc, symbol: player })
+    }
+
+    pub fn play(&mut self, next: Move) -> Result<Outcome, MoveError> {
+        // Place the move's piece on its location (index) if the move is legal,
+        // returning the outcome of the game after the move
+        let Move { player, loc, symbol } = next;
+        if self.end_game {
+            return Err(MoveError::GameOver);
+        }
+        if player != self.curr_player {
+            return Err(MoveError::NotYourTurn(player));
+        }
+        if loc >= self.total_squares() {
+            return Err(MoveError::OutOfRange(loc));
+        }
+        if !self.coordinates[loc].legal {
+            return match (self.pieces[0] | self.pieces[1]).contains(loc) {
+                true => Err(MoveError::Occupied(loc)),
+                false => Err(MoveError::Unsupported(loc)),
+            };
+        }
+        if symbol >= self.players.len() || (symbol != player && !self.wild) {
+            return Err(MoveError::WrongPiece(symbol));
+        }
+        if self.is_forbidden(symbol, loc) {
+            return Err(MoveError::Forbidden(loc));
+        }
+
+        // Update the board and coordinates
+        let x = self.coordinates[loc].x;
+        let y = self.coordinates[loc].y;
+        self.board[x][y] = self.players[symbol];
+        self.pieces[symbol] = self.pieces[symbol].with(loc);
+        self.refresh_legal();
+
+        // Record the move; a new move replaces any moves that were undone
+        self.history.push(next);
+        self.undone.clear();
+
+        // Check for endgame and change players
+        self.end_game = self.is_endgame_after(player, loc);
+        self.curr_player = self.switch_player();
+        Ok(self.outcome.clone())
+    }
+
+    pub fn set_board(&mut self, board: Vec<Vec<char>>) {
+        // Replace the pieces on the board, e.g. to set up a position for analysis,
+        // without checking the rules; the move history is cleared
+        assert!(board.len() == self.height * self.layers && board.iter().all(|row| row.len() == self.width),
+                "board must have {} rows of {} squares", self.height * self.layers, self.width);
+        self.board = board;
+        self.pieces = [Bitboard::EMPTY; 2];
+        for (loc, coord) in self.coordinates.iter().enumerate() {
+            let piece = self.board[coord.x][coord.y];
+            if let Some(player) = self.players.iter().position(|&p| p == piece) {
+                self.pieces[player] = self.pieces[player].with(loc);
+            }
+        }
+        self.refresh_legal();
+        self.history.clear();
+        self.undone.clear();
+    }
+
+    pub fn undo(&mut self) -> Option<Move> {
+        // Take back the last move, handing the turn back to the player who made it,
+        // and return it, or None if no moves have been played
+        let last = self.history.pop()?;
+        let x = self.coordinates[last.loc].x;
+        let y = self.coordinates[last.loc].y;
+        self.board[x][y] = ' ';
+        self.pieces[last.symbol] = self.pieces[last.symbol].without(last.loc);
+        self.refresh_legal();
+
+        // moves are only made while the game is in play, so taking one back reopens the game
+        self.curr_player = last.player;
+        self.end_game = false;
+        self.outcome = Outcome::InProgress;
+        self.undone.push(last);
+        Some(last)
+    }
+
+    pub fn redo(&mut self) -> Option<Move> {
+        // Replay the most recently undone move and return it, or None if nothing was undone
+        // or the rules have changed so that it can no longer be played, which also drops the moves undone before it
+        let next = self.undone.pop()?;
+        let undone = std::mem::take(&mut self.undone);
+        self.play(next).ok()?;
+        self.undone = undone;
+        Some(next)
+    }
+
+    pub fn undo_turn(&mut self) -> usize {
+        // Undo moves until it is the current player's turn again, so an opponent's reply
+        // is taken back along with the player's own move; returns the number of moves undone
+        self.rewind(Game::undo)
+    }
+
+    pub fn redo_turn(&mut self) -> usize {
+        // Redo moves until it is the current player's turn again; returns the number of moves replayed
+        self.rewind(Game::redo)
+    }
+
+    fn rewind(&mut self, step: fn(&mut Game) -> Option<Move>) -> usize {
+        // Repeat `step` until the player to move is back to the one who asked, or there is nothing left
+        let player = self.curr_player;
+        let mut steps = 0;
+        while step(self).is_some() {
+            steps += 1;
+            if self.curr_player == player {
+                break;
+            }
+        }
+        steps
+    }
+
+    pub fn is_legal(&self, loc: usize) -> bool {
+        // Returns True if the current player can place a piece on the location (index) right now, False otherwise
+        (0..self.players.len()).any(|symbol| self.can_place(loc, symbol))
+    }
+
+    pub fn can_place(&self, loc: usize, symbol: usize) -> bool {
+        // Returns True if the current player can place the piece (index into `players`) on the location (index)
+        // right now: their own piece, or either piece in wild games, on an open square where that piece is no foul.
+        // `play` accepts exactly these moves
+        !self.end_game && loc < self.total_squares() && self.coordinates[loc].legal
+            && symbol < self.players.len() && (symbol == self.curr_player || self.wild)
+            && !self.is_forbidden(symbol, loc)
+    }
+
+    pub fn is_forbidden(&self, symbol: usize, loc: usize) -> bool {
+        // Returns True if renju rules forbid placing the piece (index into `players`) on the open location (index);
+        // only player 1's piece is restricted, whoever places it
+        self.position().is_foul(loc, symbol)
+    }
+
+    pub fn legal_moves(&self) -> Vec<usize> {
+        // Returns every location (index) where the current player may place a piece
+        if self.end_game {
+            return vec![];
+        }
+        (0..self.total_squares()).filter(|&loc| self.is_legal(loc)).collect()
+    }
+
+    pub fn cells(&self) -> Vec<char> {
+        // Returns the pieces on the board flattened in location (index) order
+        self.coordinates.iter().map(|coord| self.board[coord.x][coord.y]).collect()
+    }
+
+    pub fn position(&self) -> Position {
+        // Returns an immutable snapshot of the board and turn for lookahead and analysis; it credits lines
+        // to the owner of their pieces, so it does not model wild games
+        Position::from_parts(self.width, self.height, self.layers, self.win_length, self.pieces, self.curr_player)
+            .with_rules(self.rules)
+            .with_restriction(self.restriction)
+            .with_gravity(self.gravity)
+    }
+
+    pub fn winner(&self) -> Option<usize> {
+        // Returns the player who won the game, if any
+        match self.outcome {
+            Outcome::Win { player, .. } => Some(player),
+            _ => None,
+        }
+    }
+
+    pub fn switch_player(&self) -> usize {
+        // Switch current player
+        if self.curr_player == 0 { 1 } else { 0 }
+    }
+
+    pub fn random_move(&self) -> Option<usize> {
+        // Return the location (index) for a random, legal move, or None if there is none
+        let moves = self.legal_moves();
+        match moves.len() {
+            0 => None,
+            count => Some(moves[self.rng.gen_range(0, count)]),
+        }
+    }
+
+    pub fn random_piece(&self, loc: usize) -> usize {
+        // Return the piece (index into `players`) for a random move on the location (index): in wild games,
+        // either piece that can be placed there at random, otherwise the current player's own
+        let pieces: Vec<usize> = (0..self.players.len()).filter(|&symbol| self.can_place(loc, symbol)).collect();
+        match (self.wild, pieces.len()) {
+            (true, count) if count > 0 => pieces[self.rng.gen_range(0, count)],
+            _ => self.curr_player,
+        }
+    }
+
+    pub fn is_endgame(&mut self) -> bool {
+        // Checks for end game win/draw states returning True if an endgame is reached, False otherwise,
+        // and records the outcome
+        // the position finds the completed line, which is credited to the owner of its pieces
+        let position = self.position();
+        if let Outcome::Win { line, .. } = position.outcome() {
+            let player = self.completer(position.piece_at(line[0]).expect("a completed line holds pieces"));
+            self.outcome = Outcome::Win { player: self.rules.line_winner(player), line };
+            return true;
+        }
+
+        // if the board is full, check for drawn state
+        self.outcome = if self.is_draw() { Outcome::Draw } else { Outcome::InProgress };
+        self.outcome == Outcome::Draw
+    }
+
+    pub fn is_endgame_after(&mut self, player: usize, loc: usize) -> bool {
+        // Checks for end game win/draw states after `player` placed a piece on the location (index),
+        // returning True if an endgame is reached, False otherwise, and records the outcome.
+        // Only the lines through that location can have been completed by the move, with the piece placed there.
+        self.outcome = match self.position().line_at(loc) {
+            Some(line) => Outcome::Win { player: self.rules.line_winner(player), line },
+            None if self.is_draw() => Outcome::Draw,
+            None => Outcome::InProgress,
+        };
+        self.outcome != Outcome::InProgress
+    }
+
+    fn completer(&self, symbol: usize) -> usize {
+        // Returns the player credited with a line of the piece (index into `players`): its owner,
+        // or in wild games the player who moved last, since a completed line ends the game at once
+        match self.wild {
+            true => self.history.last().map_or(self.switch_player(), |last| last.player),
+            false => symbol,
+        }
+    }
+
+    pub fn is_draw(&self) -> bool {
+        // Checks for drawn states and returns True if a drawn state is reached, False otherwise
+        // the board is drawn once it is full
+        (self.pieces[0] | self.pieces[1]).count() == self.total_squares()
+    }
+
+    pub fn is_win(&self, row: &[char]) -> Option<usize> {
+        // Checks for win states and returns the winning player if a win state is reached, None otherwise;
+        // in wild games the row is credited to the player who moved last rather than the owner of its pieces,
+        // and under misère rules the player who filled the row loses, so the other player is returned
+        let symbol = if row == &self.win_states.p1_win_state[..] {
+            0
+        } else if row == &self.win_states.p2_win_state[..] {
+            1
+        } else {
+            return None;
+        };
+        Some(self.rules.line_winner(self.completer(symbol)))
+    }
+
+    pub fn reset(&mut self) {
+        // Reset Game
+        self.board = vec![vec![' '; self.width]; self.height * self.layers];
+        self.pieces = [Bitboard::EMPTY; 2];
+        self.curr_player = 0;
+        self.end_game = false;
+        self.coordinates.clear();
+        self.coordinates = coord_mapping(self.width, self.height, self.layers);
+        self.refresh_legal();
+        self.outcome = Outcome::InProgress;
+        self.history.clear();
+        self.undone.clear();
+    }
+}
+
+pub fn coord_mapping(width: usize, height: usize, layers: usize) -> Vec<Coord> {
+    // Generates a coordinate mapping of vector indices -> coordinates on the board
+    // and `legal` represents whether a square is available for placing a piece (True)
+    // or already has a piece placed on it (False)
+    let mut coordinates: Vec<Coord> = vec![];
+    for i in 0..height * layers {
+        for j in 0..width {
+            let coord = Coord { x: i, y: j, z: i / height, legal: true };
+            coordinates.push(coord);
+        }
+    }
+    coordinates
+}
+
+pub fn line_mapping(width: usize, height: usize, layers: usize, win_length: usize) -> Vec<Vec<usize>> {
+    // Generates every run of `win_length` locations (indices) along a row, column or diagonal, within
+    // a layer or through the layers, e.g. the eight lines [0, 1, 2], ..., [2, 4, 6] of the standard
+    // 3x3 board or the 76 lines of 4x4x4 Qubic. Flat boards have no lines through the layers.
+    let mut lines: Vec<Vec<usize>> = vec![];
+    let span = win_length as isize - 1;
+    let directions = DIRECTIONS.iter().filter(|&&(layer_step, _, _)| layer_step == 0 || layers > 1);
+    for &(layer_step, row_step, col_step) in directions {
+        for layer in 0..layers as isize {
+            for row in 0..height as isize {
+                for col in 0..width as isize {
+                    // Skip runs that would fall off the board
+                    let end_layer = layer + layer_step * span;
+                    let end_row = row + row_step * span;
+                    let end_col = col + col_step * span;
+                    if end_layer >= layers as isize || end_row < 0 || end_row >= height as isize
+                        || end_col < 0 || end_col >= width as isize {
+                        continue;
+                    }
+                    let line = (0..win_length as isize)
+                        .map(|step| {
+                            let square_row = (layer + layer_step * step) * height as isize + row + row_step * step;
+                            (square_row * width as isize + col + col_step * step) as usize
+                        })
+                        .collect();
+                    lines.push(line);
+                }
+            }
+        }
+    }
+    lines
+}
+
+pub fn lines_through(total_squares: usize, lines: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    // Generates, for each location (index), the indices of the lines in `lines` that pass through it
+    let mut through: Vec<Vec<usize>> = vec![vec![]; total_squares];
+    for (line_idx, line) in lines.iter().enumerate() {
+        for &loc in line {
+            through[loc].push(line_idx);
+        }
+    }
+    through
+}
+
+pub fn board_name(width: usize, height: usize, layers: usize) -> String {
+    // Names the board's size for players, e.g. "3x3" for a flat board or "4x4x4" for a layered one
+    match layers {
+        1 => format!("{}x{}", width, height),
+        _ => format!("{}x{}x{}", width, height, layers),
+    }
+}
+
+pub fn index_width(total_squares: usize) -> usize {
+    // Returns the number of digits needed to print the largest location (index) on the board
+    (total_squares.max(2) - 1).to_string().len()
+}
+
+impl fmt::Display for Game {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        //  Display game state (allows display with macros like println!)
+        let mut game_status = format!("in play, {}'s turn", &self.players[self.curr_player]);
+        if self.end_game {
+            game_status = "ended".to_string();
+        }
+
+        let divider = "-".repeat(4 * self.width - 1);
+        writeln!(formatter, "\nGame {}:", &game_status)?;
+        for (layer, rows) in self.board.chunks(self.height).enumerate() {
+            // layered boards print each layer under its own heading, top layer first
+            if self.layers > 1 {
+                writeln!(formatter, "{}  Layer {}", if layer > 0 { "\n" } else { "" }, layer + 1)?;
+            }
+            let mut total_lines = self.height - 1;
+            for row in rows {
+                let squares: Vec<String> = row.iter().map(|square| square.to_string()).collect();
+                writeln!(formatter, "  {}", squares.join(" | "))?;
+                if total_lines > 0 {
+                    writeln!(formatter, " {}", divider)?;
+                    total_lines -= 1;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+
+/***********
+ UNIT TESTS
+************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use more_asserts::{assert_ge};
+
+    fn board_from(rows: [[char; 3]; 3]) -> Vec<Vec<char>> {
+        // Builds a 3x3 board from array literals
+        rows.iter().map(|row| row.to_vec()).collect()
+    }
+
+    #[test]
+    fn test_board_init() {
+        // Test that init board contains correct values
+        let board = board_from([[' ', ' ', ' '],
+                                [' ', ' ', ' '],
+                                [' ', ' ', ' ']]);
+        let game = Game::new();
+        assert_eq!(board, game.board);
+    }
+
+    #[test]
+    fn test_cells() {
+        // Test that the flattened board follows location (index) order
+        let mut game = Game::new();
+        game.place(5).unwrap();
+        let mut expected = vec![' '; 9];
+        expected[5] = P1;
+        assert_eq!(game.cells(), expected);
+    }
+
+    #[test]
+    fn test_game_display() {
+        // Test that init board displays correctly
+        let game = Game::new();
+        let mut game_status = format!("Game in play, {}'s turn", &game.players[game.curr_player]);
+        if game.end_game {
+            game_status = "Game ended".to_string();
+        }
+        let mut expect_board = format!("\n{}:", &game_status);
+        expect_board += "\n    |   |  \n -----------\n    |   |  \n -----------\n    |   |  \n";
+        assert_eq!(expect_board, format!("{}", game));
+    }
+
+    #[test]
+    fn test_auto_move_reaches_endgame() {
+        // Should result in an endgame within Game const `SIZE * SIZE` moves,
+        // otherwise, there is a halting error, which is caught by the while loop's
+        // `max_moves > -2` stopping condition. If this stopping condition is used
+        // the assert will fail.
+        let mut max_moves = (SIZE * SIZE) as isize;
+        let mut game = Game::new();
+        game.set_seed(7);
+
+        while !game.end_game && (max_moves > -2) {
+            let loc = game.random_move().unwrap();
+            game.place(loc).unwrap();
+            max_moves -= 1;
+        }
+        assert_ge!(max_moves, 0)
+    }
+
+    #[test]
+    fn test_place() {
+        // Tests that placing a piece fills the square, marks it illegal and hands over the turn
+        let mut game = Game::new();
+        assert_eq!(game.place(4), Ok(Outcome::InProgress));
+        assert_eq!(game.board[1][1], P1);
+        assert!(!game.coordinates[4].legal);
+        assert_eq!(game.curr_player, 1);
+    }
+
+    #[test]
+    fn test_line_mapping_standard() {
+        // Tests that the standard board generates its eight rows, columns and diagonals
+        let lines = line_mapping(3, 3, 1, 3);
+        assert_eq!(lines, vec![vec![0, 1, 2], vec![3, 4, 5], vec![6, 7, 8],
+                               vec![0, 3, 6], vec![1, 4, 7], vec![2, 5, 8],
+                               vec![0, 4, 8], vec![2, 4, 6]]);
+    }
+
+    #[test]
+    fn test_line_mapping_sizes() {
+        // Tests the number of winning lines on larger and rectangular boards
+        assert_eq!(line_mapping(4, 4, 1, 4).len(), 10);
+        assert_eq!(line_mapping(7, 7, 1, 4).len(), 88);
+        assert_eq!(line_mapping(4, 3, 1, 3).len(), 14);
+        assert_eq!(line_mapping(5, 1, 1, 3), vec![vec![0, 1, 2], vec![1, 2, 3], vec![2, 3, 4]]);
+    }
+
+    #[test]
+    fn test_exact_ignores_overlines() {
+        // Tests that four in a row does not complete a line of three when overlines do not count
+        let play = |restriction: Restriction| -> Game {
+            let mut game = Game::with_size(6, 2, 3);
+            game.set_restriction(restriction);
+            for &loc in &[0, 6, 1, 9, 3, 11, 2] {
+                game.place(loc).unwrap();
+            }
+            game
+        };
+        assert_eq!(play(Restriction::Freestyle).outcome, Outcome::Win { player: 0, line: vec![0, 1, 2] });
+        let game = play(Restriction::Exact);
+        assert_eq!(game.outcome, Outcome::InProgress);
+        assert_eq!(game.position().outcome(), Outcome::InProgress);
+    }
+
+    #[test]
+    fn test_renju_fouls() {
+        // Tests that a double three is rejected for player 1 only, and only under renju rules
+        let mut game = Game::with_size(15, 15, 5);
+        for &loc in &[110, 0, 111, 2, 82, 4, 97, 6] {
+            game.place(loc).unwrap();
+        }
+        assert!(game.is_legal(112));
+        game.set_restriction(Restriction::Renju);
+        assert!(!game.is_legal(112));
+        assert!(!game.legal_moves().contains(&112));
+        assert!(!game.is_forbidden(1, 112));
+        assert_eq!(game.place(112), Err(MoveError::Forbidden(112)));
+        assert_eq!(game.position().try_apply(112), Err(MoveError::Forbidden(112)));
+        assert_eq!(game.curr_player, 0);
+    }
+
+    #[test]
+    fn test_wild_renju_fouls() {
+        // Tests that in wild games renju fouls restrict player 1's piece, so the square stays open to the other piece
+        let mut game = Game::with_size(15, 15, 5);
+        for &loc in &[110, 0, 111, 2, 82, 4, 97, 6] {
+            game.place(loc).unwrap();
+        }
+        game.set_restriction(Restriction::Renju);
+        game.set_wild(true);
+        assert!(!game.can_place(112, 0));
+        assert!(game.can_place(112, 1));
+        assert!(game.is_legal(112));
+        assert_eq!(game.random_piece(112), 1);
+        assert_eq!(game.place(112), Err(MoveError::Forbidden(112)));
+        assert_eq!(game.place_symbol(112, 1), Ok(Outcome::InProgress));
+    }
+
+    #[test]
+    fn test_notation() {
+        // Tests that squares are named by column letter and row number counted from the bottom
+        let game = Game::with_size(15, 15, 5);
+        assert_eq!(game.notation(112), Some("h8".to_string()));
+        assert_eq!(game.notation(210), Some("a1".to_string()));
+        assert_eq!(game.notation(14), Some("o15".to_string()));
+        assert_eq!(game.parse_notation("H8"), Some(112));
+        assert_eq!(game.parse_notation(" o15\n"), Some(14));
+        assert_eq!(game.parse_notation("p1"), None);
+        assert_eq!(game.parse_notation("a16"), None);
+        assert_eq!(game.parse_notation("a0"), None);
+        assert_eq!(game.parse_notation("8h"), None);
+        assert_eq!(Game::with_layers(3, 3, 3, 3).notation(0), None);
+    }
+
+    #[test]
+    fn test_gravity() {
+        // Tests that under gravity pieces drop to the bottom of their column and only those squares are legal
+        let mut game = Game::with_size(7, 6, 4);
+        game.set_gravity(true);
+        assert_eq!(game.legal_moves(), (35..42).collect::<Vec<usize>>());
+        assert_eq!(game.place(0), Err(MoveError::Unsupported(0)));
+        assert_eq!(game.drop_location(3), Some(38));
+        game.drop(3).unwrap();
+        assert_eq!(game.board[5][3], P1);
+        assert_eq!(game.drop_location(3), Some(31));
+        assert!(game.is_legal(31) && !game.is_legal(38));
+        assert_eq!(game.position().legal_moves(), game.legal_moves());
+        assert_eq!(game.position().try_apply(24), Err(MoveError::Unsupported(24)));
+
+        game.undo();
+        assert_eq!(game.legal_moves(), (35..42).collect::<Vec<usize>>());
+        for _ in 0..6 {
+            game.drop(0).unwrap();
+        }
+        assert_eq!(game.drop(0), Err(MoveError::ColumnFull(0)));
+        assert_eq!(game.drop(7), Err(MoveError::ColumnFull(7)));
+        assert!(!game.end_game);
+    }
+
+    #[test]
+    fn test_gravity_win() {
+        // Tests that four in a column wins Connect Four, and that reset keeps the gravity
+        let mut game = Game::with_size(7, 6, 4);
+        game.set_gravity(true);
+        for &column in &[0, 1, 0, 1, 0, 1, 0] {
+            game.drop(column).unwrap();
+        }
+        assert_eq!(game.outcome, Outcome::Win { player: 0, line: vec![14, 21, 28, 35] });
+        game.reset();
+        assert!(game.gravity);
+        assert_eq!(game.legal_moves().len(), 7);
+    }
+
+    #[test]
+    fn test_wild_credits_completer() {
+        // Tests that in a wild game either piece may be placed and a line wins for whoever completes it
+        let mut game = Game::new();
+        assert_eq!(game.place_symbol(0, 1), Err(MoveError::WrongPiece(1)));
+        game.set_wild(true);
+        for &(loc, symbol) in &[(0, 0), (4, 1), (1, 1), (8, 1)] {
+            game.place_symbol(loc, symbol).unwrap();
+        }
+        assert!(!game.end_game);
+        assert_eq!(game.place_symbol(2, 0), Ok(Outcome::InProgress));
+        assert_eq!(game.place_symbol(3, 2), Err(MoveError::WrongPiece(2)));
+        assert_eq!(game.place_symbol(3, 0), Ok(Outcome::InProgress));
+        assert_eq!(game.place_symbol(5, 1), Ok(Outcome::InProgress));
+        assert_eq!(game.place_symbol(6, 0), Ok(Outcome::Win { player: 1, line: vec![0, 3, 6] }));
+        assert_eq!(game.board[2][0], P1);
+        assert_eq!(game.is_win(&[P1, P1, P1]), Some(1));
+        assert!(game.is_endgame());
+        assert_eq!(game.winner(), Some(1));
+    }
+
+    #[test]
+    fn test_wild_undo() {
+        // Tests that undoing and redoing a wild move restores the piece that was placed
+        let mut game = Game::new();
+        game.set_wild(true);
+        game.place_symbol(4, 1).unwrap();
+        game.place_symbol(0, 1).unwrap();
+        game.place_symbol(8, 1).unwrap();
+        assert_eq!(game.outcome, Outcome::Win { player: 0, line: vec![0, 4, 8] });
+        assert_eq!(game.undo(), Some(Move { player: 0, loc: 8, symbol: 1 }));
+        assert!(game.pieces[1].contains(4) && !game.pieces[1].contains(8));
+        assert_eq!(game.redo(), Some(Move { player: 0, loc: 8, symbol: 1 }));
+        assert_eq!(game.board[2][2], P2);
+        assert_eq!(game.winner(), Some(0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_with_size_win_length_too_long() {
+        // Tests that a win length that cannot fit on the board is rejected
+        let _game = Game::with_size(3, 3, 4);
+    }
+
+    #[test]
+    fn test_try_with_layers() {
+        // Tests that boards which cannot be played are rejected with the reason instead of a panic
+        assert!(Game::try_with_layers(3, 3, 1, 3).is_ok_and(|game| game.lines.len() == 8));
+        assert_eq!(Game::try_with_layers(3, 0, 1, 3), Err(BoardError::Empty));
+        assert_eq!(Game::try_with_layers(16, 16, 2, 5), Err(BoardError::TooLarge(512)));
+        assert_eq!(Game::try_with_layers(usize::MAX, 2, 1, 3), Err(BoardError::TooLarge(usize::MAX)));
+        let error = Game::try_with_layers(3, 3, 1, 4).unwrap_err();
+        assert_eq!(error, BoardError::WinLength { win_length: 4, width: 3, height: 3, layers: 1 });
+        assert_eq!(error.to_string(), "win length 4 does not fit on a 3x3 board");
+    }
+
+    #[test]
+    fn test_with_size_win() {
+        // Tests that four in a row wins on a 4x4 board while three in a row does not
+        let mut game = Game::with_size(4, 4, 4);
+        assert_eq!(game.coordinates.len(), 16);
+
+        for &(x_loc, o_loc) in &[(0, 4), (5, 8), (10, 12)] {
+            game.place(x_loc).unwrap();
+            game.place(o_loc).unwrap();
+        }
+        assert!(!game.end_game);
+        assert_eq!(game.place(15), Ok(Outcome::Win { player: 0, line: vec![0, 5, 10, 15] }));
+        assert!(game.end_game);
+        assert_eq!(game.winner(), Some(0));
+    }
+
+    #[test]
+    fn test_with_size_draw() {
+        // Tests that a full rectangular board with no run of `win_length` is drawn
+        let mut game = Game::with_size(4, 2, 3);
+        for &loc in &[0, 1, 4, 5, 2, 3, 6, 7] {
+            assert!(!game.end_game);
+            game.place(loc).unwrap();
+        }
+        assert!(game.end_game);
+        assert_eq!(game.outcome, Outcome::Draw);
+    }
+
+    #[test]
+    fn test_with_size_display() {
+        // Tests that the display scales with the board width and height
+        let game = Game::with_size(4, 2, 2);
+        let expect_board = "\nGame in play, X's turn:\n    |   |   |  \n ---------------\n    |   |   |  \n";
+        assert_eq!(expect_board, format!("{}", game));
+    }
+
+    #[test]
+    fn test_layered_lines() {
+        // Tests that layered boards add the lines through their layers: 49 on 3x3x3 and Qubic's 76 on 4x4x4
+        assert_eq!(line_mapping(3, 3, 3, 3).len(), 49);
+        let qubic = line_mapping(4, 4, 4, 4);
+        assert_eq!(qubic.len(), 76);
+        assert!(qubic.contains(&vec![5, 21, 37, 53]));     // straight down
+        assert!(qubic.contains(&vec![3, 22, 41, 60]));     // corner to corner
+        assert!(qubic.iter().all(|line| line.iter().all(|&loc| loc < 64)));
+        assert_eq!(Game::with_layers(4, 4, 4, 4).lines_through[0].len(), 7);
+        assert_eq!(line_mapping(2, 1, 2, 2), vec![vec![0, 1], vec![2, 3], vec![0, 2], vec![1, 3], vec![0, 3], vec![1, 2]]);
+    }
+
+    #[test]
+    fn test_layered_win() {
+        // Tests that a line through every layer wins, and that coordinates name the layer
+        let mut game = Game::with_layers(4, 4, 4, 4);
+        assert_eq!(game.total_squares(), 64);
+        assert_eq!(game.location(3, 1, 2), Some(54));
+        assert_eq!(game.location(4, 0, 0), None);
+        assert_eq!((game.coordinates[54].x, game.coordinates[54].y, game.coordinates[54].z), (13, 2, 3));
+        for &(x_loc, o_loc) in &[(0, 1), (21, 2), (42, 3)] {
+            game.place(x_loc).unwrap();
+            game.place(o_loc).unwrap();
+        }
+        assert_eq!(game.board[13][2], ' ');
+        assert_eq!(game.place(63), Ok(Outcome::Win { player: 0, line: vec![0, 21, 42, 63] }));
+        assert_eq!(game.board[15][3], P1);
+        assert_eq!(game.position().outcome(), game.outcome);
+    }
+
+    #[test]
+    fn test_layered_display() {
+        // Tests that each layer is displayed under its own heading
+        let mut game = Game::with_layers(2, 1, 2, 2);
+        game.place(3).unwrap();
+        let expect_board = "\nGame in play, O's turn:\n  Layer 1\n    |  \n\n  Layer 2\n    | X\n";
+        assert_eq!(expect_board, format!("{}", game));
+        assert_eq!(board_name(4, 4, 4), "4x4x4");
+        assert_eq!(board_name(4, 3, 1), "4x3");
+    }
+
+    #[test]
+    fn test_index_width() {
+        // Tests the digits needed to print board indices
+        assert_eq!(index_width(9), 1);
+        assert_eq!(index_width(16), 2);
+        assert_eq!(index_width(225), 3);
+    }
+
+    #[test]
+    fn test_move_errors() {
+        // Tests that illegal moves are rejected without changing the game
+        let mut game = Game::new();
+        assert_eq!(game.place(9), Err(MoveError::OutOfRange(9)));
+        assert_eq!(game.make_move(1, 0), Err(MoveError::NotYourTurn(1)));
+
+        game.place(0).unwrap();
+        assert_eq!(game.place(0), Err(MoveError::Occupied(0)));
+        assert_eq!(game.board[0][0], P1);
+        assert_eq!(game.curr_player, 1);
+
+        for &loc in &[3, 1, 4, 2] {
+            game.place(loc).unwrap();
+        }
+        assert_eq!(game.outcome, Outcome::Win { player: 0, line: vec![0, 1, 2] });
+        assert_eq!(game.place(8), Err(MoveError::GameOver));
+    }
+
+    #[test]
+    fn test_legal_moves() {
+        // Tests that occupied squares, and every square once the game ends, are not legal
+        let mut game = Game::new();
+        assert_eq!(game.legal_moves(), (0..9).collect::<Vec<usize>>());
+
+        game.place(4).unwrap();
+        game.place(0).unwrap();
+        assert_eq!(game.legal_moves(), vec![1, 2, 3, 5, 6, 7, 8]);
+        assert!(!game.is_legal(4));
+        assert!(!game.is_legal(9));
+        assert!(game.is_legal(8));
+
+        for &loc in &[1, 2, 7] {
+            game.place(loc).unwrap();
+        }
+        assert!(game.end_game);
+        assert_eq!(game.legal_moves(), vec![]);
+        assert!(!game.is_legal(8));
+    }
+
+    #[test]
+    fn test_end_game_outcome() {
+        // Tests that is_endgame records a draw separately from a game still in progress
+        let mut game = Game::new();
+        game.set_board(board_from([['X', 'O', 'X'],
+                                       ['X', 'O', 'O'],
+                                       ['O', 'X', 'X']]));
+        assert!(game.is_endgame());
+        assert_eq!(game.outcome, Outcome::Draw);
+
+        let mut board = game.board.clone();
+        board[2][2] = ' ';
+        game.set_board(board);
+        assert!(!game.is_endgame());
+        assert_eq!(game.outcome, Outcome::InProgress);
+    }
+
+    #[test]
+    fn test_is_win_false() {
+        // Tests that the function returns a `false` to signify a win has not occurred
+        // for various scenarios
+        let game = Game::new();
+
+        assert_eq!(game.is_win(&[P1, P2, P2]), None);
+        assert_eq!(game.is_win(&[P1, P1, P2]), None);
+        assert_eq!(game.is_win(&[P2, P1, P2]), None);
+        assert_eq!(game.is_win(&[' ', P1, P1]), None);
+        assert_eq!(game.is_win(&[P1, ' ', P1]), None);
+        assert_eq!(game.is_win(&[P2, P2, ' ']), None);
+    }
+
+    #[test]
+    fn test_is_win_true() {
+        // Tests that the function returns a `true` to signify a win has occurred
+        let game = Game::new();
+
+        assert!(game.is_win(&[P1, P1, P1]).is_some());
+        assert!(game.is_win(&[P2, P2, P2]).is_some());
+    }
+
+    #[test]
+    fn test_is_win_none() {
+        // Test for correct default value when there is no winner
+        let mut game = Game::new();
+        game.set_board(board_from([[' ', P1, ' '],
+                                       [' ', P2, ' '],
+                                       [' ', ' ', ' ']]));
+        assert!(!game.is_endgame());
+        assert_eq!(game.outcome, Outcome::InProgress);
+        assert_eq!(game.winner(), None);
+    }
+
+    #[test]
+    fn test_p2_win_state() {
+        // Test for correct winner when P2 wins
+        let game = Game::new();
+        let winner = game.is_win(&[P2, P2, P2]).unwrap();
+        assert_eq!(game.players[winner], P2);
+    }
+
+    #[test]
+    fn test_p1_win_state() {
+        // Test for correct winner when P1 wins
+        let game = Game::new();
+        let winner = game.is_win(&[P1, P1, P1]).unwrap();
+        assert_eq!(game.players[winner], P1);
+    }
+
+    #[test]
+    fn test_misere_win_state() {
+        // Under misère rules the player who fills a row loses it to the other player
+        let mut game = Game::new();
+        game.set_rules(Rules::Misere);
+        assert_eq!(game.is_win(&[P1, P1, P1]), Some(1));
+        for &loc in &[0, 3, 1, 4, 2] {
+            game.place(loc).unwrap();
+        }
+        assert_eq!(game.outcome, Outcome::Win { player: 1, line: vec![0, 1, 2] });
+        assert_eq!(game.position().outcome(), game.outcome);
+        game.set_rules(Rules::Standard);
+        assert_eq!(game.winner(), Some(0));
+    }
+
+    #[test]
+    fn test_reset() {
+        // Tests if the game resets correctly to its original values after being played
+        // by comparing with another unplayed game instantiated with the same initial values
+        let mut original_game = Game::new();
+        original_game.set_seed(7);
+
+        while !original_game.end_game {
+            let loc = original_game.random_move().unwrap();
+            original_game.place(loc).unwrap();
+        }
+        original_game.reset();
+
+        let mut comparison_game = Game::new();
+        comparison_game.set_seed(7);
+        assert_eq!(original_game, comparison_game);
+    }
+
+    #[test]
+    fn test_seed_replays_game() {
+        // Two games from the same seed make the same random moves
+        let play = |seed: u64| -> Vec<Vec<char>> {
+            let mut game = Game::with_size(5, 5, 4);
+            game.set_seed(seed);
+            while !game.end_game {
+                let loc = game.random_move().unwrap();
+                game.place(loc).unwrap();
+            }
+            game.board
+        };
+        assert_eq!(play(42), play(42));
+        assert_ne!(play(42), play(43));
+    }
+
+    #[test]
+    fn test_lines_through() {
+        // Tests that each location knows the lines through it, e.g. the centre of 3x3 is on four
+        let game = Game::new();
+        assert_eq!(game.lines_through[4].len(), 4);
+        assert_eq!(game.lines_through[1].len(), 2);
+        assert!(game.lines_through[4].iter().all(|&line_idx| game.lines[line_idx].contains(&4)));
+    }
+
+    #[test]
+    fn test_win_through_last_move() {
+        // Tests that a win is found through the last move and reports the winning cells
+        let mut game = Game::with_size(7, 7, 4);
+        for &loc in &[10, 0, 18, 1, 26, 2] {
+            game.place(loc).unwrap();
+        }
+        assert_eq!(game.place(34), Ok(Outcome::Win { player: 0, line: vec![10, 18, 26, 34] }));
+    }
+
+    #[test]
+    fn test_incremental_outcome_matches_full_check() {
+        // Tests that checking only the last move's lines agrees with checking the whole board
+        for seed in 0..20 {
+            let mut game = Game::with_size(6, 5, 4);
+            game.set_seed(seed);
+            while !game.end_game {
+                let loc = game.random_move().unwrap();
+                game.place(loc).unwrap();
+                assert_eq!(game.outcome, game.position().outcome());
+            }
+        }
+    }
+
+    #[test]
+    fn test_undo_restores_position() {
+        // Tests that undoing a winning move reopens the game for the player who made it
+        let mut game = Game::new();
+        for &loc in &[0, 3, 1, 4, 2] {
+            game.place(loc).unwrap();
+        }
+        assert_eq!(game.undo(), Some(Move { player: 0, loc: 2, symbol: 0 }));
+        assert_eq!(game.board[0][2], ' ');
+        assert!(game.is_legal(2));
+        assert_eq!(game.curr_player, 0);
+        assert!(!game.end_game);
+        assert_eq!(game.outcome, Outcome::InProgress);
+        assert_eq!(game.history.len(), 4);
+    }
+
+    #[test]
+    fn test_redo_replays_moves() {
+        // Tests that redo replays undone moves in order, including the win
+        let mut game = Game::new();
+        for &loc in &[0, 3, 1, 4, 2] {
+            game.place(loc).unwrap();
+        }
+        let finished = game.board.clone();
+        while game.undo().is_some() {}
+        assert_eq!(game.board, Game::new().board);
+        assert_eq!(game.curr_player, 0);
+        assert_eq!(game.undone.len(), 5);
+
+        while game.redo().is_some() {}
+        assert_eq!(game.board, finished);
+        assert_eq!(game.winner(), Some(0));
+        assert_eq!(game.curr_player, 1);
+        assert!(game.end_game);
+    }
+
+    #[test]
+    fn test_move_clears_redo() {
+        // Tests that a new move discards the moves that were undone
+        let mut game = Game::new();
+        game.place(0).unwrap();
+        game.place(4).unwrap();
+        game.undo();
+        game.place(8).unwrap();
+        assert_eq!(game.redo(), None);
+        assert_eq!(game.history, vec![Move { player: 0, loc: 0, symbol: 0 }, Move { player: 1, loc: 8, symbol: 1 }]);
+    }
+
+    #[test]
+    fn test_redo_after_rule_change() {
+        // Tests that a move the new rules no longer allow is not replayed, and nothing undone before it is either
+        let mut game = Game::new();
+        game.place(0).unwrap();
+        game.place(4).unwrap();
+        game.undo_turn();
+        game.set_gravity(true);
+        assert_eq!(game.redo(), None);
+        assert_eq!(game.redo(), None);
+        assert!(game.history.is_empty());
+        assert_eq!(game.curr_player, 0);
+    }
+
+    #[test]
+    fn test_undo_turn_skips_reply() {
+        // Tests that undoing a turn takes back the opponent's reply too, and redoing replays both
+        let mut game = Game::new();
+        for &loc in &[4, 0, 8] {
+            game.place(loc).unwrap();
+        }
+        assert_eq!(game.undo_turn(), 2);
+        assert_eq!(game.history, vec![Move { player: 0, loc: 4, symbol: 0 }]);
+        assert_eq!(game.curr_player, 1);
+        assert_eq!(game.redo_turn(), 2);
+        assert_eq!(game.history.len(), 3);
+        assert_eq!(game.curr_player, 1);
+    }
+
+    #[test]
+    fn test_undo_empty_history() {
+        // Tests that there is nothing to undo or redo in a new game
+        let mut game = Game::new();
+        assert_eq!(game.undo(), None);
+        assert_eq!(game.redo(), None);
+    }
+
+    #[test]
+    fn test_reset_reopens_game() {
+        // Tests that a reset game is back in play rather than flagged as ended
+        let mut game = Game::new();
+        game.end_game = true;
+        game.reset();
+        assert!(!game.end_game);
+    }
+
+    #[test]
+    fn test_is_draw_true() {
+        // Tests that the function returns a `true` to signify a draw has occurred
+        let mut game = Game::new();
+
+        game.set_board(board_from([['O', 'X', 'O'],
+                                       ['O', 'X', 'X'],
+                                       ['X', 'O', 'X']]));
+        assert!(game.is_draw());
+
+        game.set_board(board_from([['X', 'X', 'O'],
+                                       ['O', 'O', 'X'],
+                                       ['X', 'X', 'O']]));
+        assert!(game.is_draw());
+    }
+
+    #[test]
+    fn test_is_draw_false() {
+        // Tests that the function returns a `false` to signify a draw has not occurred
+        let mut game = Game::new();
+
+        game.set_board(board_from([['X', 'O', 'O'],
+                                       ['O', 'X', ' '],
+                                       ['X', 'X', ' ']]));
+        assert!(!game.is_draw());
+
+        game.set_board(board_from([[' ', ' ', ' '],
+                                       [' ', ' ', ' '],
+                                       [' ', ' ', ' ']]));
+        assert!(!game.is_draw());
+
+        game.set_board(board_from([['O', 'X', 'X'],
+                                       ['O', 'X', 'O'],
+                                       [' ', ' ', 'X']]));
+        assert!(!game.is_draw());
+    }
+
+    #[test]
+    fn test_switch_player() {
+        // Tests that players are switched correctly
+        let mut game = Game::new();
+        game.curr_player = 0;
+        assert_eq!(game.switch_player(), 1);
+
+        game.curr_player = 1;
+        assert_eq!(game.switch_player(), 0);
+    }
+
+    #[test]
+    fn test_end_game_true() {
+        // Tests that is_endgame returns True if a win/drawn state is reached
+        let mut game = Game::new();
+
+        // drawn end game
+        game.set_board(board_from([['X', 'O', 'O'],
+                                       ['O', 'X', 'X'],
+                                       ['X', 'O', 'X']]));
+        assert!(game.is_endgame());
+
+        // won end game
+        game.set_board(board_from([['X', 'O', 'O'],
+                                       ['O', 'X', 'O'],
+                                       ['X', 'X', 'X']]));
+        assert!(game.is_endgame());
+    }
+
+    #[test]
+    fn test_end_game_false() {
+        // Tests that is_endgame returns False if the board is incomplete with no win
+        let mut game = Game::new();
+
+        game.set_board(board_from([['O', ' ', 'O'],
+                                       ['O', 'X', 'X'],
+                                       ['X', 'O', 'X']]));
+        assert!(!game.is_endgame());
+
+        game.set_board(board_from([['O', 'X', 'X'],
+                                       [' ', ' ', 'X'],
+                                       [' ', ' ', 'O']]));
+        assert!(!game.is_endgame());
+
+        game.set_board(board_from([['X', 'O', 'O'],
+                                       ['X', 'O', 'X'],
+                                       [' ', 'X', ' ']]));
+        assert!(!game.is_endgame());
+    }
+}
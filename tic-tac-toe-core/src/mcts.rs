@@ -0,0 +1,378 @@
+/*
+*  Monte Carlo Tree Search (UCT): grows a tree of promising moves, scoring each new position by a
+*  random playout to the end of the game. Works on any board size, since the work per move is
+*  bounded by the iteration count or time limit rather than the size of the game tree.
+*/
+
+use std::time::Duration;
+use rand::rngs::StdRng;
+use rand::{thread_rng, Rng, SeedableRng};
+use crate::timer::Timer;
+use crate::position::Position;
+use crate::{Difficulty, Game, Rules};
+
+pub const MCTS_ITERATIONS: u64 = 10_000;    // default playouts per move
+const DRAW_REWARD: f64 = 0.5;               // playout reward for a drawn game
+
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MctsConfig {
+    // Budget and tuning for one search; the search stops at whichever limit is reached first
+    pub iterations: Option<u64>,    // playouts to run, `None` for no limit
+    pub max_time: Option<Duration>, // time to search for, `None` for no limit
+    pub exploration: f64,           // UCT exploration constant, higher values try more moves
+    pub seed: Option<u64>,          // random seed for reproducible play, `None` for a fresh seed
+}
+
+impl Default for MctsConfig {
+    fn default() -> MctsConfig {
+        // A fixed number of playouts with the textbook exploration constant
+        MctsConfig { iterations: Some(MCTS_ITERATIONS), max_time: None,
+                     exploration: std::f64::consts::SQRT_2, seed: None }
+    }
+}
+
+impl MctsConfig {
+    pub fn for_difficulty(difficulty: Difficulty) -> MctsConfig {
+        // The difficulty's playouts with the textbook exploration constant
+        MctsConfig { iterations: Some(difficulty.playouts()), ..MctsConfig::default() }
+    }
+
+    pub fn tuned(self, playouts: Option<u64>, max_time: Option<Duration>, exploration: Option<f64>) -> MctsConfig {
+        // Overrides the settings that are given: `playouts` replaces the playout budget, and a time limit
+        // without one lifts the budget so the search runs for that long
+        MctsConfig {
+            iterations: playouts.or(self.iterations.filter(|_| max_time.is_none())),
+            max_time: max_time.or(self.max_time),
+            exploration: exploration.unwrap_or(self.exploration),
+            ..self
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MctsResult {
+    // Move chosen by a search and the statistics behind it
+    pub best_move: Option<usize>,   // most visited location (index), None if the game is over
+    pub iterations: u64,            // playouts run
+    pub win_rate: f64,              // average reward of the best move for the player to move
+}
+
+#[derive(Debug)]
+struct Node {
+    // One position in the search tree
+    loc: Option<usize>,     // move that led here, None for the root
+    mover: usize,           // player who made that move
+    parent: Option<usize>,  // index of the parent node
+    children: Vec<usize>,   // indices of expanded children
+    untried: Vec<usize>,    // legal moves not yet expanded
+    terminal: Option<f64>,  // reward for `mover` if the game ended here
+    visits: u64,
+    reward: f64,            // total playout reward for `mover`
+}
+
+pub struct Mcts {
+    // Searcher for one board geometry
+    config: MctsConfig,
+    rng: StdRng,
+    line_reward: f64,                   // reward for the player who completes a line: 1 normally, 0 in misère
+}
+
+impl Mcts {
+    pub fn new(game: &Game, config: MctsConfig) -> Mcts {
+        // Builds a searcher for the game's board, seeding its generator from `config`
+        let seed = config.seed.unwrap_or_else(|| thread_rng().gen());
+        Mcts {
+            config,
+            rng: StdRng::seed_from_u64(seed),
+            line_reward: match game.rules {
+                Rules::Standard => 1.0,
+                Rules::Misere => 0.0,
+            },
+        }
+    }
+
+    pub fn search(&mut self, game: &Game) -> MctsResult {
+        // Runs playouts from the current position until the budget is spent
+        // and returns the most visited move
+        if game.end_game {
+            return MctsResult { best_move: None, iterations: 0, win_rate: 0.0 };
+        }
+        let root = game.position();
+        let mut tree = vec![Node {
+            loc: None,
+            mover: 1 - game.curr_player,
+            parent: None,
+            children: vec![],
+            untried: game.legal_moves(),
+            terminal: None,
+            visits: 0,
+            reward: 0.0,
+        }];
+
+        let timer = Timer::start();
+        // every search runs at least one playout, so it finds a move however small its budget
+        let mut iterations = 0;
+        while iterations == 0 || !self.out_of_budget(iterations, &timer) {
+            self.iterate(&mut tree, root);
+            iterations += 1;
+        }
+
+        let best = tree[0].children.iter()
+            .copied()
+            .max_by_key(|&child| tree[child].visits);
+        MctsResult {
+            best_move: best.and_then(|child| tree[child].loc),
+            iterations,
+            win_rate: best.map_or(0.0, |child| tree[child].reward / tree[child].visits.max(1) as f64),
+        }
+    }
+
+    fn iterate(&mut self, tree: &mut Vec<Node>, root: Position) {
+        // One round of selection, expansion, playout and backpropagation
+        let mut position = root;
+        let mut node = 0;
+
+        // selection: follow the best UCT child until a node still has untried moves
+        while tree[node].untried.is_empty() && tree[node].terminal.is_none() && !tree[node].children.is_empty() {
+            node = self.select_child(tree, node);
+            let loc = tree[node].loc.expect("only the root has no move");
+            position = position.with_piece(loc, tree[node].mover);
+        }
+
+        // expansion: add one untried move as a new child
+        if tree[node].terminal.is_none() && !tree[node].untried.is_empty() {
+            let pick = self.rng.gen_range(0, tree[node].untried.len());
+            let loc = tree[node].untried.swap_remove(pick);
+            let mover = 1 - tree[node].mover;
+            position = position.with_piece(loc, mover);
+
+            let terminal = if position.completes_line(loc) {
+                Some(self.line_reward)
+            } else if position.is_full() {
+                Some(DRAW_REWARD)
+            } else {
+                None
+            };
+            let untried = match terminal {
+                Some(_) => vec![],
+                None => position.playable_moves(),
+            };
+            tree.push(Node { loc: Some(loc), mover, parent: Some(node), children: vec![],
+                             untried, terminal, visits: 0, reward: 0.0 });
+            let child = tree.len() - 1;
+            tree[node].children.push(child);
+            node = child;
+        }
+
+        // playout: finish the game at random, scoring it for the player who moved into `node`
+        let reward = match tree[node].terminal {
+            Some(reward) => reward,
+            None => self.playout(position, tree[node].mover),
+        };
+
+        // backpropagation: alternate the reward's point of view on the way back up
+        let mut current = Some(node);
+        let mut reward = reward;
+        while let Some(index) = current {
+            tree[index].visits += 1;
+            tree[index].reward += reward;
+            reward = 1.0 - reward;
+            current = tree[index].parent;
+        }
+    }
+
+    fn select_child(&self, tree: &[Node], node: usize) -> usize {
+        // Picks the child with the highest upper confidence bound
+        let log_visits = (tree[node].visits.max(1) as f64).ln();
+        let exploration = self.config.exploration;
+        let uct = |child: usize| {
+            let visits = tree[child].visits.max(1) as f64;
+            tree[child].reward / visits + exploration * (log_visits / visits).sqrt()
+        };
+        tree[node].children.iter()
+            .copied()
+            .fold(None, |best: Option<(usize, f64)>, child| {
+                let score = uct(child);
+                match best {
+                    Some((_, best_score)) if best_score >= score => best,
+                    _ => Some((child, score)),
+                }
+            })
+            .map(|(child, _)| child)
+            .expect("selection only visits expanded nodes")
+    }
+
+    fn playout(&mut self, mut position: Position, last_mover: usize) -> f64 {
+        // Plays random moves to the end of the game, returning the reward for `last_mover`
+        let mut open: Vec<usize> = (0..position.total_squares())
+            .filter(|&loc| position.piece_at(loc).is_none() && position.is_supported(loc))
+            .collect();
+        let mut mover = last_mover;
+        while !open.is_empty() {
+            mover = 1 - mover;
+            // fouls are set aside until the mover has found a legal square, and a player with none draws
+            let mut fouls = vec![];
+            let loc = loop {
+                if open.is_empty() {
+                    return DRAW_REWARD;
+                }
+                let pick = self.rng.gen_range(0, open.len());
+                let loc = open.swap_remove(pick);
+                if !position.is_foul(loc, mover) {
+                    break loc;
+                }
+                fouls.push(loc);
+            };
+            open.extend(fouls);
+            open.extend(position.square_above(loc));
+            position = position.with_piece(loc, mover);
+            if position.completes_line(loc) {
+                return if mover == last_mover { self.line_reward } else { 1.0 - self.line_reward };
+            }
+        }
+        DRAW_REWARD
+    }
+
+    fn out_of_budget(&self, iterations: u64, timer: &Timer) -> bool {
+        // Returns True once the iteration or time budget has been used up;
+        // a search with no limits at all runs the default number of iterations
+        let iteration_limit = match (self.config.iterations, self.config.max_time) {
+            (None, None) => Some(MCTS_ITERATIONS),
+            (limit, _) => limit,
+        };
+        iteration_limit.is_some_and(|limit| iterations >= limit)
+            || self.config.max_time.is_some_and(|max_time| timer.elapsed() >= max_time)
+    }
+}
+
+pub fn mcts_move(game: &Game, config: MctsConfig) -> Option<usize> {
+    // Returns the most promising location (index) for the current player
+    Mcts::new(game, config).search(game).best_move
+}
+
+
+/***********
+ UNIT TESTS
+************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::{AiPlayer, Player};
+    use crate::{Difficulty, Strategy};
+
+    fn play(game: &mut Game, moves: &[usize]) {
+        // Plays the given locations in order
+        for &loc in moves {
+            game.place(loc).unwrap();
+        }
+    }
+
+    fn seeded(iterations: u64, seed: u64) -> MctsConfig {
+        // A reproducible configuration with a fixed number of playouts
+        MctsConfig { iterations: Some(iterations), seed: Some(seed), ..MctsConfig::default() }
+    }
+
+    #[test]
+    fn test_mcts_takes_win() {
+        // X completes the top row
+        let mut game = Game::new();
+        play(&mut game, &[0, 3, 1, 4]);
+        assert_eq!(mcts_move(&game, seeded(2_000, 1)), Some(2));
+    }
+
+    #[test]
+    fn test_mcts_gravity_takes_win() {
+        // In Connect Four, X drops a fourth piece onto its column
+        let mut game = Game::with_size(7, 6, 4);
+        game.set_gravity(true);
+        play(&mut game, &[35, 36, 28, 37, 21, 40]);
+        assert_eq!(mcts_move(&game, seeded(2_000, 1)), Some(14));
+    }
+
+    #[test]
+    fn test_mcts_misere_avoids_line() {
+        // Under misère rules completing the top row would lose, so X plays elsewhere
+        let mut game = Game::new();
+        game.set_rules(Rules::Misere);
+        play(&mut game, &[0, 3, 1, 4]);
+        assert_ne!(mcts_move(&game, seeded(2_000, 1)), Some(2));
+    }
+
+    #[test]
+    fn test_mcts_blocks_loss() {
+        // O must block X's top row
+        let mut game = Game::new();
+        play(&mut game, &[0, 4, 1]);
+        assert_eq!(mcts_move(&game, seeded(2_000, 1)), Some(2));
+    }
+
+    #[test]
+    fn test_mcts_seed_is_reproducible() {
+        // The same seed gives the same statistics, move and iteration count
+        let game = Game::with_size(5, 5, 4);
+        let first = Mcts::new(&game, seeded(500, 42)).search(&game);
+        let second = Mcts::new(&game, seeded(500, 42)).search(&game);
+        assert_eq!(first, second);
+        assert_eq!(first.iterations, 500);
+    }
+
+    #[test]
+    fn test_mcts_time_limit() {
+        // A time limit alone stops the search and still yields a legal move
+        let game = Game::with_size(7, 7, 4);
+        let config = MctsConfig { iterations: None, max_time: Some(Duration::from_millis(20)),
+                                  ..seeded(0, 3) };
+        let result = Mcts::new(&game, config).search(&game);
+        assert!(result.iterations > 0);
+        assert!(game.is_legal(result.best_move.unwrap()));
+    }
+
+    #[test]
+    fn test_mcts_strategy_never_loses_to_random() {
+        // An MCTS player finishes every game against random moves without losing, as either player
+        for mcts_player in 0..2 {
+            for seed in 0..5 {
+                let mut game = Game::new();
+                game.set_seed(seed);
+                let mut players = [AiPlayer::new(Strategy::Random, Difficulty::Random); 2];
+                players[mcts_player] = AiPlayer::new(Strategy::Mcts, Difficulty::Perfect);
+                while !game.end_game {
+                    let loc = players[game.curr_player].choose_move(&game).unwrap();
+                    game.place(loc).unwrap();
+                }
+                assert_ne!(game.winner(), Some(1 - mcts_player), "seed {}", seed);
+            }
+        }
+    }
+
+    #[test]
+    fn test_tuned_config() {
+        // Tuning replaces the given settings, and a time limit alone replaces the playout budget
+        let medium = MctsConfig::for_difficulty(Difficulty::Medium);
+        assert_eq!(medium.iterations, Some(1_000));
+        assert_eq!(medium.tuned(None, None, None), medium);
+        assert_eq!(medium.tuned(Some(50), None, Some(0.5)),
+                   MctsConfig { iterations: Some(50), exploration: 0.5, ..medium });
+        let timed = medium.tuned(None, Some(Duration::from_millis(20)), None);
+        assert_eq!((timed.iterations, timed.max_time), (None, Some(Duration::from_millis(20))));
+        assert_eq!(medium.tuned(Some(50), Some(Duration::from_millis(20)), None).iterations, Some(50));
+    }
+
+    #[test]
+    fn test_mcts_empty_budget() {
+        // A search with no playouts to spend still runs one and finds a move
+        let game = Game::new();
+        let result = Mcts::new(&game, seeded(0, 1)).search(&game);
+        assert_eq!(result.iterations, 1);
+        assert!(game.is_legal(result.best_move.unwrap()));
+    }
+
+    #[test]
+    fn test_mcts_game_over() {
+        // No move is offered once the game has ended
+        let mut game = Game::new();
+        play(&mut game, &[0, 3, 1, 4, 2]);
+        assert_eq!(mcts_move(&game, seeded(100, 1)), None);
+    }
+}
@@ -0,0 +1,493 @@
+/*
+*  Notakto: both players place X on one or more 3x3 boards. A board with three in a row is dead and takes
+*  no more moves, and whoever kills the last live board loses. Each board is a `Game`, so its line check
+*  decides when it dies. Perfect play follows the misère quotient of 3x3 Notakto (Plambeck and Whitehead):
+*  every board position maps to an element of an 18-element commutative monoid, the elements of all the
+*  boards multiply to the element of the whole game, and the player to move loses exactly when that
+*  product is one of the four P-positions a, b², bc and c².
+*/
+
+use std::fmt;
+use std::ops::Mul;
+use crate::rng::SeededRng;
+use crate::symmetry::Symmetry;
+use crate::{Difficulty, Game, Move, MoveError, Outcome, SIZE};
+
+pub const SQUARES: usize = SIZE * SIZE;    // squares on each board
+
+const BOARD_VALUES: [(u16, Quotient); 46] = [   // quotient element of each live board, keyed by its canonical pieces
+    (0, C), (1, ONE), (2, ONE), (3, AD), (5, B), (10, A), (11, B), (12, B), (13, A), (14, D), (16, CC), (17, B),
+    (18, B), (19, AB), (21, A), (26, AB), (27, A), (28, A), (29, B), (30, B), (40, A), (41, D), (42, B), (43, A),
+    (45, B), (68, A), (69, AB), (70, D), (78, AB), (97, A), (98, ONE), (99, B), (101, B), (102, A), (106, AB),
+    (108, A), (110, B), (113, B), (114, B), (115, A), (170, A), (171, B), (173, A), (229, A), (238, A), (325, A),
+];
+const ONE: Quotient = Quotient::new(0, 0, 0, 0);
+const A: Quotient = Quotient::new(1, 0, 0, 0);
+const B: Quotient = Quotient::new(0, 1, 0, 0);
+const AB: Quotient = Quotient::new(1, 1, 0, 0);
+const C: Quotient = Quotient::new(0, 0, 1, 0);
+const CC: Quotient = Quotient::new(0, 0, 2, 0);
+const D: Quotient = Quotient::new(0, 0, 0, 1);
+const AD: Quotient = Quotient::new(1, 0, 0, 1);
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Quotient {
+    // An element of the misère quotient of 3x3 Notakto, the monoid generated by a, b, c and d with
+    // a² = 1, b³ = b, b²c = c, c³ = ac², b²d = d, cd = ad and d² = c², kept in its reduced form
+    a: u8,  // power of a: 0 or 1
+    b: u8,  // power of b: 0 to 2, and at most 1 alongside c or d
+    c: u8,  // power of c: 0 to 2, and 0 alongside d
+    d: u8,  // power of d: 0 or 1
+}
+
+impl Quotient {
+    const fn new(a: u8, b: u8, c: u8, d: u8) -> Quotient {
+        // Builds an element from powers that are already reduced
+        Quotient { a, b, c, d }
+    }
+
+    fn reduced(mut a: u8, mut b: u8, mut c: u8, mut d: u8) -> Quotient {
+        // Applies the monoid's relations until no power can shrink any further
+        loop {
+            let before = (a, b, c, d);
+            if d >= 2 {
+                (c, d) = (c + 2, d - 2);
+            }
+            if c >= 1 && d >= 1 {
+                (a, c) = (a + 1, c - 1);
+            }
+            if c >= 3 {
+                (a, c) = (a + 1, c - 1);
+            }
+            if b >= 3 || (b >= 2 && (c >= 1 || d >= 1)) {
+                b -= 2;
+            }
+            a %= 2;
+            if (a, b, c, d) == before {
+                return Quotient { a, b, c, d };
+            }
+        }
+    }
+
+    pub fn is_p_position(self) -> bool {
+        // Returns True if the player to move loses a game of this value with perfect play
+        [A, Quotient::new(0, 2, 0, 0), Quotient::new(0, 1, 1, 0), CC].contains(&self)
+    }
+}
+
+impl Mul for Quotient {
+    type Output = Quotient;
+
+    fn mul(self, other: Quotient) -> Quotient {
+        // Multiplies two elements, as when their games are played side by side
+        Quotient::reduced(self.a + other.a, self.b + other.b, self.c + other.c, self.d + other.d)
+    }
+}
+
+impl fmt::Display for Quotient {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        // Name the element by its generators, e.g. "1", "ad" or "bc²"
+        let power = |generator: &str, power: u8| match power {
+            0 => String::new(),
+            1 => generator.to_string(),
+            _ => format!("{}²", generator),
+        };
+        let name = power("a", self.a) + &power("b", self.b) + &power("c", self.c) + &power("d", self.d);
+        write!(formatter, "{}", if name.is_empty() { "1" } else { &name })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Notakto {
+    // Boards played side by side with X only: locations (indices) count the boards in order, `board * SQUARES + square`
+    pub boards: Vec<Game>,      // 3x3 boards, in location (index) order, each dead once it has three in a row
+    pub curr_player: usize,     // current player
+    pub end_game: bool,         // game status: False if in play, True once the last board is dead
+    pub outcome: Outcome,       // whether the game is in play or won, with the line that killed the last board
+    pub rng: SeededRng,         // source of every random move, seeded so games can be replayed
+    pub history: Vec<Move>,     // moves played so far, in order
+}
+
+impl Notakto {
+    pub fn new(boards: usize) -> Self {
+        // Initializes `boards` empty 3x3 boards with player 1 to move
+        assert!(boards > 0, "notakto needs at least one board");
+        Self {
+            boards: (0..boards).map(|_| Game::new()).collect(),
+            curr_player: 0,
+            end_game: false,
+            outcome: Outcome::InProgress,
+            rng: SeededRng::from_entropy(),
+            history: vec![],
+        }
+    }
+
+    pub fn set_seed(&mut self, seed: u64) {
+        // Restart the random number generator from `seed` so random moves can be replayed
+        self.rng = SeededRng::new(seed);
+    }
+
+    pub fn seed(&self) -> u64 {
+        // Returns the seed of the random number generator
+        self.rng.seed()
+    }
+
+    pub fn total_squares(&self) -> usize {
+        // Returns the number of locations (indices) across every board
+        self.boards.len() * SQUARES
+    }
+
+    pub fn location(&self, board: usize, square: usize) -> Option<usize> {
+        // Returns the location (index) of `square` on `board`, each counted from 0, or None if off the boards
+        match board < self.boards.len() && square < SQUARES {
+            true => Some(board * SQUARES + square),
+            false => None,
+        }
+    }
+
+    pub fn piece_at(&self, loc: usize) -> char {
+        // Returns the piece on the location (index), or a space if it is empty
+        self.boards[loc / SQUARES].cells()[loc % SQUARES]
+    }
+
+    pub fn is_dead(&self, board: usize) -> bool {
+        // Returns True if the board has three in a row and takes no more moves
+        self.boards[board].end_game
+    }
+
+    pub fn live_boards(&self) -> Vec<usize> {
+        // Returns the boards that can still be played on
+        (0..self.boards.len()).filter(|&board| !self.is_dead(board)).collect()
+    }
+
+    pub fn is_legal(&self, loc: usize) -> bool {
+        // Returns True if an X can be placed on the location (index) right now, False otherwise
+        !self.end_game && loc < self.total_squares() && self.boards[loc / SQUARES].is_legal(loc % SQUARES)
+    }
+
+    pub fn legal_moves(&self) -> Vec<usize> {
+        // Returns every empty location (index) on a live board
+        self.live_boards().into_iter()
+            .flat_map(|board| self.boards[board].legal_moves().into_iter().map(move |square| board * SQUARES + square))
+            .collect()
+    }
+
+    pub fn random_move(&self) -> Option<usize> {
+        // Return the location (index) for a random, legal move, or None if there is none
+        let moves = self.legal_moves();
+        match moves.len() {
+            0 => None,
+            count => Some(moves[self.rng.gen_range(0, count)]),
+        }
+    }
+
+    pub fn place(&mut self, loc: usize) -> Result<Outcome, MoveError> {
+        // Place an X on the location (index) for the current player and hand the turn over
+        self.make_move(self.curr_player, loc)
+    }
+
+    pub fn make_move(&mut self, player: usize, loc: usize) -> Result<Outcome, MoveError> {
+        // Place an X on the location (index) for `player` if the move is legal, returning the outcome:
+        // killing the last live board loses the game
+        if self.end_game {
+            return Err(MoveError::GameOver);
+        }
+        if player != self.curr_player {
+            return Err(MoveError::NotYourTurn(player));
+        }
+        if loc >= self.total_squares() {
+            return Err(MoveError::OutOfRange(loc));
+        }
+        let (board, square) = (loc / SQUARES, loc % SQUARES);
+        if self.is_dead(board) {
+            return Err(MoveError::WrongBoard(board));
+        }
+
+        // Every piece is an X, so each board is played as its first player
+        let small = &mut self.boards[board];
+        small.curr_player = 0;
+        let small_outcome = small.place(square).map_err(|_| MoveError::Occupied(loc))?;
+        self.history.push(Move { player, loc, symbol: 0 });
+
+        // The game ends when no live board is left, and the player who killed it loses
+        if let Outcome::Win { line, .. } = small_outcome {
+            if self.live_boards().is_empty() {
+                let line = line.iter().map(|&square| board * SQUARES + square).collect();
+                self.outcome = Outcome::Win { player: 1 - player, line };
+                self.end_game = true;
+            }
+        }
+        self.curr_player = 1 - player;
+        Ok(self.outcome.clone())
+    }
+
+    pub fn board_value(&self, board: usize) -> Quotient {
+        // Returns the quotient element of the board on its own: the identity once it is dead
+        match self.is_dead(board) {
+            true => ONE,
+            false => pieces_value(board_pieces(&self.boards[board])),
+        }
+    }
+
+    pub fn value(&self) -> Quotient {
+        // Returns the quotient element of the whole game, the product of every board's element
+        (0..self.boards.len()).fold(ONE, |value, board| value * self.board_value(board))
+    }
+
+    pub fn winning_moves(&self) -> Vec<usize> {
+        // Returns the locations (indices) that leave the opponent in a P-position, none if the current
+        // player loses against perfect play
+        self.legal_moves().into_iter()
+            .filter(|&loc| {
+                let (board, square) = (loc / SQUARES, loc % SQUARES);
+                let value = (0..self.boards.len())
+                    .filter(|&other| other != board)
+                    .fold(pieces_value(board_pieces(&self.boards[board]) | 1 << square), |value, other| {
+                        value * self.board_value(other)
+                    });
+                value.is_p_position()
+            })
+            .collect()
+    }
+
+    pub fn best_move(&self) -> Option<usize> {
+        // Returns one of the winning locations (indices), picked with the game's seeded generator,
+        // or None if there is none
+        let moves = self.winning_moves();
+        match moves.len() {
+            0 => None,
+            count => Some(moves[self.rng.gen_range(0, count)]),
+        }
+    }
+
+    pub fn choose_move(&self, difficulty: Difficulty) -> Option<usize> {
+        // Returns the location (index) an automatic player at `difficulty` plays: a winning move
+        // unless the difficulty calls for a mistake, otherwise a random one; None once the game is over
+        if self.end_game {
+            return None;
+        }
+        if self.rng.gen_bool(difficulty.mistake_rate()) {
+            return self.random_move();
+        }
+        self.best_move().or_else(|| self.random_move())
+    }
+
+    pub fn winner(&self) -> Option<usize> {
+        // Returns the player who won the game, if any
+        match self.outcome {
+            Outcome::Win { player, .. } => Some(player),
+            _ => None,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        // Reset Game
+        for small in &mut self.boards {
+            small.reset();
+        }
+        self.curr_player = 0;
+        self.end_game = false;
+        self.outcome = Outcome::InProgress;
+        self.history.clear();
+    }
+}
+
+fn board_pieces(board: &Game) -> u16 {
+    // Returns the board's pieces as a bitmask of its squares
+    (0..SQUARES).filter(|&square| board.pieces[0].contains(square)).fold(0, |pieces, square| pieces | 1 << square)
+}
+
+fn pieces_value(pieces: u16) -> Quotient {
+    // Returns the quotient element of a board holding `pieces`, the identity if they make three in a row
+    let canonical = canonical(pieces);
+    match BOARD_VALUES.binary_search_by_key(&canonical, |&(key, _)| key) {
+        Ok(index) => BOARD_VALUES[index].1,
+        Err(_) => ONE,
+    }
+}
+
+fn canonical(pieces: u16) -> u16 {
+    // Returns the smallest bitmask among the rotations and reflections of the board's pieces
+    Symmetry::ALL.iter()
+        .map(|symmetry| {
+            (0..SQUARES)
+                .filter(|&square| pieces >> square & 1 == 1)
+                .fold(0, |image, square| image | 1 << symmetry.map(square, SIZE, SIZE))
+        })
+        .min()
+        .unwrap_or(pieces)
+}
+
+impl fmt::Display for Notakto {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        // Display the boards side by side, each under a heading that marks the dead ones
+        let game_status = match self.end_game {
+            true => "ended".to_string(),
+            false => format!("in play, player {}'s turn", self.curr_player + 1),
+        };
+        writeln!(formatter, "\nGame {}:", game_status)?;
+        let headings: Vec<String> = (0..self.boards.len())
+            .map(|board| {
+                let heading = format!("{} {}", if self.is_dead(board) { "Dead" } else { "Board" }, board + 1);
+                format!("{:<width$}", heading, width = 4 * SIZE - 3)
+            })
+            .collect();
+        writeln!(formatter, "  {}", headings.join("  ||  ").trim_end())?;
+        let divider = vec!["-".repeat(4 * SIZE - 1); self.boards.len()].join("-||-");
+        for row in 0..SIZE {
+            let boards: Vec<String> = (0..self.boards.len())
+                .map(|board| {
+                    let squares: Vec<String> = (0..SIZE)
+                        .map(|col| self.piece_at(board * SQUARES + row * SIZE + col).to_string())
+                        .collect();
+                    squares.join(" | ")
+                })
+                .collect();
+            writeln!(formatter, "  {}", boards.join("  ||  "))?;
+            if row + 1 < SIZE {
+                writeln!(formatter, " {}", divider)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+
+/***********
+ UNIT TESTS
+************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use crate::{line_mapping, P1};
+
+    fn is_p_position(boards: &[u16], known: &mut HashMap<Vec<u16>, bool>) -> bool {
+        // Solves a game of live boards holding the given pieces by searching every move
+        let mut key: Vec<u16> = boards.iter().map(|&pieces| canonical(pieces)).collect();
+        key.sort();
+        if let Some(&result) = known.get(&key) {
+            return result;
+        }
+        let lines: Vec<u16> = line_mapping(SIZE, SIZE, 1, SIZE).iter()
+            .map(|line| line.iter().fold(0, |mask, &square| mask | 1 << square))
+            .collect();
+        let result = !boards.is_empty() && (0..boards.len()).all(|board| {
+            (0..SQUARES).filter(|&square| boards[board] >> square & 1 == 0).all(|square| {
+                let pieces = boards[board] | 1 << square;
+                let mut next = boards.to_vec();
+                if lines.iter().any(|&line| line & !pieces == 0) {
+                    next.remove(board);
+                } else {
+                    next[board] = pieces;
+                }
+                !is_p_position(&next, known)
+            })
+        });
+        known.insert(key, result);
+        result
+    }
+
+    #[test]
+    fn test_quotient_relations() {
+        // The reduced elements obey the monoid's relations and there are eighteen of them
+        let (b2, c2) = (B * B, C * C);
+        assert_eq!(A * A, ONE);
+        assert_eq!(b2 * B, B);
+        assert_eq!(b2 * C, C);
+        assert_eq!(c2 * C, A * c2);
+        assert_eq!(b2 * D, D);
+        assert_eq!(C * D, AD);
+        assert_eq!(D * D, c2);
+        let generators = [A, B, C, D];
+        let mut elements = vec![ONE];
+        while let Some(next) = elements.iter()
+            .flat_map(|&x| generators.iter().map(move |&generator| x * generator))
+            .find(|product| !elements.contains(product)) {
+            elements.push(next);
+        }
+        assert_eq!(elements.len(), 18);
+        assert_eq!(elements.iter().filter(|element| element.is_p_position()).count(), 4);
+        assert_eq!((B * C * C).to_string(), "bc²");
+        assert_eq!(ONE.to_string(), "1");
+    }
+
+    #[test]
+    fn test_values_match_search() {
+        // The product of the boards' elements agrees with a full search on every game of up to three boards
+        let mut known = HashMap::new();
+        let live: Vec<u16> = BOARD_VALUES.iter().map(|&(pieces, _)| pieces).collect();
+        for (i, &first) in live.iter().enumerate() {
+            assert_eq!(pieces_value(first).is_p_position(), is_p_position(&[first], &mut known));
+            for (j, &second) in live.iter().enumerate().skip(i) {
+                let value = pieces_value(first) * pieces_value(second);
+                assert_eq!(value.is_p_position(), is_p_position(&[first, second], &mut known));
+                for &third in &live[j..] {
+                    let value = value * pieces_value(third);
+                    assert_eq!(value.is_p_position(), is_p_position(&[first, second, third], &mut known));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_dead_boards() {
+        // A board with three in a row takes no more moves, and killing the last board loses
+        let mut game = Notakto::new(2);
+        for &loc in &[0, 1, 2] {
+            game.place(loc).unwrap();
+        }
+        assert!(game.is_dead(0));
+        assert_eq!(game.live_boards(), vec![1]);
+        assert_eq!(game.place(4), Err(MoveError::WrongBoard(0)));
+        assert_eq!(game.board_value(0), ONE);
+        assert_eq!(game.legal_moves(), (9..18).collect::<Vec<usize>>());
+        for &loc in &[13, 9] {
+            game.place(loc).unwrap();
+        }
+        assert_eq!(game.place(13), Err(MoveError::Occupied(13)));
+        assert_eq!(game.place(17), Ok(Outcome::Win { player: 0, line: vec![9, 13, 17] }));
+        assert_eq!(game.piece_at(17), P1);
+        assert_eq!(game.winner(), Some(0));
+        assert!(game.legal_moves().is_empty());
+        assert_eq!(game.random_move(), None);
+    }
+
+    #[test]
+    fn test_perfect_play_wins() {
+        // The first player wins on one board and the second on two, whatever a random opponent does
+        for (boards, winner) in [(1, 0), (2, 1), (3, 0)] {
+            assert_eq!(Notakto::new(boards).value().is_p_position(), winner == 1);
+            for seed in 0..10 {
+                let mut game = Notakto::new(boards);
+                game.set_seed(seed);
+                while !game.end_game {
+                    let loc = match game.curr_player == winner {
+                        true => game.choose_move(Difficulty::Perfect).unwrap(),
+                        false => game.random_move().unwrap(),
+                    };
+                    game.place(loc).unwrap();
+                }
+                assert_eq!(game.winner(), Some(winner));
+            }
+        }
+    }
+
+    #[test]
+    fn test_display() {
+        // The boards are shown side by side, with the dead ones marked
+        let mut game = Notakto::new(2);
+        for &loc in &[0, 4, 8, 10] {
+            game.place(loc).unwrap();
+        }
+        let shown = format!("{}", game);
+        let lines: Vec<&str> = shown.lines().collect();
+        assert_eq!(lines[1], "Game in play, player 1's turn:");
+        assert_eq!(lines[2], "  Dead 1     ||  Board 2");
+        assert_eq!(lines[3], "  X |   |    ||    | X |  ");
+        assert_eq!(lines[5], "    | X |    ||    |   |  ");
+        game.reset();
+        assert_eq!(game.legal_moves().len(), 18);
+    }
+}
@@ -0,0 +1,265 @@
+/*
+*  Players pick the moves for one side of a game. Front ends hold a `Player` for each side and ask it
+*  for a move on its turn, so new bots plug in without any change to `Game`.
+*/
+
+use crate::{ai, mcts, search, tablebase, Difficulty, Game, Strategy, SEARCH_NODES};
+
+
+pub trait Player {
+    // A participant choosing moves from a read-only view of the game
+    fn name(&self) -> String;                                   // label for rendering, e.g. "hard" or "manual"
+    fn choose_move(&mut self, game: &Game) -> Option<usize>;    // location (index) to play, None to ask the user
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Human;
+
+impl Player for Human {
+    fn name(&self) -> String {
+        // Humans are labelled by how they play
+        "manual".to_string()
+    }
+
+    fn choose_move(&mut self, _game: &Game) -> Option<usize> {
+        // Moves are typed by the user, so the front end asks for them
+        None
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RandomPlayer;
+
+impl Player for RandomPlayer {
+    fn name(&self) -> String {
+        // Random players are labelled by how they play
+        "random".to_string()
+    }
+
+    fn choose_move(&mut self, game: &Game) -> Option<usize> {
+        // Plays any legal location (index), with nothing to play once the game has ended
+        game.random_move()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AiPlayer {
+    // Searching player whose strength is set by its difficulty
+    pub strategy: Strategy,         // how the player searches for its moves
+    pub difficulty: Difficulty,     // how deep it searches and how often it blunders
+    pub mcts: Option<mcts::MctsConfig>, // budget and tuning of a Monte Carlo tree search, None to follow the difficulty
+}
+
+impl AiPlayer {
+    pub fn new(strategy: Strategy, difficulty: Difficulty) -> AiPlayer {
+        // Builds a player searching by `strategy` at `difficulty`
+        AiPlayer { strategy, difficulty, mcts: None }
+    }
+
+    pub fn with_mcts(difficulty: Difficulty, config: mcts::MctsConfig) -> AiPlayer {
+        // Builds a player searching by Monte Carlo tree search with `config` in place of the difficulty's
+        // playouts, still blundering as often as `difficulty` does; without a seed in `config`, each
+        // search is seeded from the game's generator
+        AiPlayer { strategy: Strategy::Mcts, difficulty, mcts: Some(config) }
+    }
+
+    pub fn supports(&self, game: &Game) -> bool {
+        // Returns True if the player can choose its moves in the game within its budget. Only random play
+        // handles wild games, which no search can score, and minimax, which walks every move sequence
+        // up to its depth, runs out on large boards
+        match (self.strategy, self.difficulty) {
+            (_, Difficulty::Random) | (Strategy::Random, _) => true,
+            _ if game.wild => false,
+            (Strategy::Minimax, Difficulty::Perfect) if tablebase::covers(&game.position()) => true,
+            (Strategy::Minimax, difficulty) => ai::supports(game, difficulty.search_depth()),
+            (Strategy::AlphaBeta, _) | (Strategy::Mcts, _) => true,
+        }
+    }
+}
+
+impl Player for AiPlayer {
+    fn name(&self) -> String {
+        // Names the difficulty, and the strategy only when it is not the default alpha-beta search
+        match (self.difficulty, self.strategy) {
+            (Difficulty::Random, _) | (_, Strategy::Random) => "random".to_string(),
+            (difficulty, Strategy::AlphaBeta) => difficulty.to_string(),
+            (difficulty, Strategy::Minimax) => format!("{} minimax", difficulty),
+            (difficulty, Strategy::Mcts) => format!("{} mcts", difficulty),
+        }
+    }
+
+    fn choose_move(&mut self, game: &Game) -> Option<usize> {
+        // Returns the location (index) chosen by the strategy,
+        // or a random one when the difficulty calls for a mistake,
+        // drawing every random number from the game's seeded generator.
+        // Perfect searches look small boards up in the tablebase instead of searching them.
+        // Returns None once the game has ended and for games the player does not `supports`,
+        // e.g. wild games for any search, rather than quietly moving at random
+        if game.legal_moves().is_empty() || !self.supports(game) {
+            return None;
+        }
+        if game.rng.gen_bool(self.difficulty.mistake_rate()) {
+            return game.random_move();
+        }
+        match self.strategy {
+            Strategy::Random => game.random_move(),
+            Strategy::Minimax | Strategy::AlphaBeta if self.difficulty == Difficulty::Perfect
+                                                       && tablebase::covers(&game.position()) => {
+                tablebase::best_move(game)
+            },
+            Strategy::Minimax => ai::minimax_move(game, self.difficulty.search_depth()),
+            Strategy::AlphaBeta => {
+                let limits = search::SearchLimits::nodes(SEARCH_NODES);
+                let limits = match self.difficulty.search_depth() {
+                    Some(max_depth) => limits.depth(max_depth),
+                    None => limits,
+                };
+                search::alpha_beta_move(game, limits)
+            },
+            Strategy::Mcts => {
+                let config = self.mcts.unwrap_or_else(|| mcts::MctsConfig::for_difficulty(self.difficulty));
+                let seed = config.seed.unwrap_or_else(|| game.rng.next_u64());
+                mcts::mcts_move(game, mcts::MctsConfig { seed: Some(seed), ..config })
+            },
+        }
+    }
+}
+
+
+/***********
+ UNIT TESTS
+************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn play_out(game: &mut Game, players: &mut [Box<dyn Player>; 2]) {
+        // Lets both players move until the game ends
+        while !game.end_game {
+            let loc = players[game.curr_player].choose_move(game).unwrap();
+            game.place(loc).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_names() {
+        // Each player describes itself for rendering
+        assert_eq!(Human.name(), "manual");
+        assert_eq!(RandomPlayer.name(), "random");
+        assert_eq!(AiPlayer::new(Strategy::AlphaBeta, Difficulty::Hard).name(), "hard");
+        assert_eq!(AiPlayer::new(Strategy::Minimax, Difficulty::Medium).name(), "medium minimax");
+        assert_eq!(AiPlayer::new(Strategy::Random, Difficulty::Perfect).name(), "random");
+        assert_eq!(AiPlayer::new(Strategy::Mcts, Difficulty::Random).name(), "random");
+    }
+
+    #[test]
+    fn test_supports() {
+        // Minimax only takes on boards it can search within its budget at its difficulty,
+        // and only random play takes on wild games
+        let gomoku = Game::with_size(15, 15, 5);
+        assert!(AiPlayer::new(Strategy::Minimax, Difficulty::Perfect).supports(&Game::new()));
+        assert!(AiPlayer::new(Strategy::Minimax, Difficulty::Medium).supports(&gomoku));
+        assert!(!AiPlayer::new(Strategy::Minimax, Difficulty::Hard).supports(&gomoku));
+        assert!(!AiPlayer::new(Strategy::Minimax, Difficulty::Perfect).supports(&Game::with_size(4, 4, 4)));
+        assert!(AiPlayer::new(Strategy::AlphaBeta, Difficulty::Perfect).supports(&gomoku));
+        let mut wild = Game::new();
+        wild.set_wild(true);
+        assert!(!AiPlayer::new(Strategy::AlphaBeta, Difficulty::Easy).supports(&wild));
+        assert!(AiPlayer::new(Strategy::Mcts, Difficulty::Random).supports(&wild));
+    }
+
+    #[test]
+    fn test_with_mcts() {
+        // A tuned MCTS player plays with its own budget and replays the same move from the same seed
+        let config = mcts::MctsConfig { iterations: Some(200), exploration: 1.0, ..mcts::MctsConfig::default() };
+        let mut player = AiPlayer::with_mcts(Difficulty::Perfect, config);
+        assert_eq!((player.strategy, player.name()), (Strategy::Mcts, "perfect mcts".to_string()));
+        let mut game = Game::new();
+        for &loc in &[0, 3, 1, 4] {
+            game.place(loc).unwrap();
+        }
+        assert_eq!(player.choose_move(&game), Some(2));
+        let mut replay = || {
+            let mut game = Game::with_size(5, 5, 4);
+            game.set_seed(3);
+            player.choose_move(&game)
+        };
+        assert_eq!(replay(), replay());
+    }
+
+    #[test]
+    fn test_human_waits_for_input() {
+        // A human never moves on their own
+        assert_eq!(Human.choose_move(&Game::new()), None);
+    }
+
+    #[test]
+    fn test_no_move_after_game_over() {
+        // Automatic players have nothing to play once the game has ended
+        let mut game = Game::new();
+        for &loc in &[0, 3, 1, 4, 2] {
+            game.place(loc).unwrap();
+        }
+        assert_eq!(RandomPlayer.choose_move(&game), None);
+        assert_eq!(AiPlayer::new(Strategy::AlphaBeta, Difficulty::Perfect).choose_move(&game), None);
+    }
+
+    #[test]
+    fn test_searches_refuse_wild_games() {
+        // Searches leave wild games alone instead of moving at random, while random play still moves
+        let mut game = Game::new();
+        game.set_wild(true);
+        assert_eq!(AiPlayer::new(Strategy::AlphaBeta, Difficulty::Hard).choose_move(&game), None);
+        assert_eq!(AiPlayer::new(Strategy::Minimax, Difficulty::Perfect).choose_move(&game), None);
+        assert_eq!(AiPlayer::new(Strategy::Mcts, Difficulty::Easy).choose_move(&game), None);
+        assert!(game.is_legal(AiPlayer::new(Strategy::Random, Difficulty::Perfect).choose_move(&game).unwrap()));
+        assert!(game.is_legal(AiPlayer::new(Strategy::Mcts, Difficulty::Random).choose_move(&game).unwrap()));
+    }
+
+    #[test]
+    fn test_perfect_difficulty_never_loses() {
+        // The perfect tier never loses the standard game to the easy tier
+        for _round in 0..5 {
+            let mut game = Game::new();
+            let mut players: [Box<dyn Player>; 2] = [Box::new(AiPlayer::new(Strategy::AlphaBeta, Difficulty::Easy)),
+                                                     Box::new(AiPlayer::new(Strategy::AlphaBeta, Difficulty::Perfect))];
+            play_out(&mut game, &mut players);
+            assert_ne!(game.winner(), Some(0));
+        }
+    }
+
+    #[test]
+    fn test_seeded_players_replay() {
+        // Players that blunder at random still replay the same game from the same seed
+        let play = |seed: u64| -> Vec<Vec<char>> {
+            let mut game = Game::with_size(4, 4, 3);
+            game.set_seed(seed);
+            let mut players: [Box<dyn Player>; 2] = [Box::new(AiPlayer::new(Strategy::AlphaBeta, Difficulty::Easy)),
+                                                     Box::new(AiPlayer::new(Strategy::Mcts, Difficulty::Medium))];
+            play_out(&mut game, &mut players);
+            game.board
+        };
+        assert_eq!(play(11), play(11));
+    }
+
+    #[test]
+    fn test_custom_player() {
+        // A bot written outside the engine plays through the same trait
+        struct FirstOpenSquare;
+        impl Player for FirstOpenSquare {
+            fn name(&self) -> String {
+                "first open square".to_string()
+            }
+
+            fn choose_move(&mut self, game: &Game) -> Option<usize> {
+                game.legal_moves().first().copied()
+            }
+        }
+
+        let mut game = Game::new();
+        let mut players: [Box<dyn Player>; 2] = [Box::new(FirstOpenSquare), Box::new(RandomPlayer)];
+        play_out(&mut game, &mut players);
+        assert!(game.end_game);
+        assert_eq!(game.board[0][0], game.players[0]);
+    }
+}
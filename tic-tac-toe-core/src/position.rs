@@ -0,0 +1,505 @@
+/*
+*  Immutable snapshot of a game. A `Position` is a small `Copy` value with pure rules functions,
+*  so searches and analysis can explore hypothetical moves without touching the live `Game`.
+*/
+
+use crate::bitboard::{Bitboard, MAX_SQUARES};
+use crate::symmetry::{symmetries, Symmetry};
+use crate::renju::Renju;
+use crate::{board_name, MoveError, Outcome, Restriction, Rules, DIRECTIONS};
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Position {
+    // Board geometry, pieces and turn, with each player's pieces stored one bit per location (index)
+    width: usize,               // number of columns on the board
+    height: usize,              // number of rows in each layer of the board
+    layers: usize,              // number of layers stacked into the board, 1 for a flat board
+    win_length: usize,          // number of pieces in a row needed to win
+    rules: Rules,               // whether completing a line wins or loses
+    restriction: Restriction,   // whether overlines count and which moves are fouls for player 1
+    gravity: bool,              // True if pieces drop to the lowest open square of their column
+    pieces: [Bitboard; 2],      // locations (indices) of each player's pieces
+    to_move: usize,             // player whose turn it is
+}
+
+impl Position {
+    pub fn new(width: usize, height: usize, win_length: usize) -> Position {
+        // The empty `width` x `height` board won by `win_length` in a row under standard rules,
+        // with player 1 to move
+        Position::layered(width, height, 1, win_length)
+    }
+
+    pub fn layered(width: usize, height: usize, layers: usize, win_length: usize) -> Position {
+        // The empty board of `layers` stacked `width` x `height` layers won by `win_length` in a row,
+        // within a layer or through the layers, under standard rules with player 1 to move
+        assert!(width > 0 && height > 0 && layers > 0, "board must have at least one row, column and layer");
+        assert!(width * height * layers <= MAX_SQUARES, "a position holds at most {} squares", MAX_SQUARES);
+        assert!(win_length > 0 && win_length <= width.max(height).max(layers),
+                "win length {} does not fit on a {} board", win_length, board_name(width, height, layers));
+        Position { width, height, layers, win_length, rules: Rules::Standard, restriction: Restriction::Freestyle,
+                   gravity: false, pieces: [Bitboard::EMPTY; 2], to_move: 0 }
+    }
+
+    pub fn with_rules(self, rules: Rules) -> Position {
+        // Returns the same position played under `rules`, e.g. misère
+        Position { rules, ..self }
+    }
+
+    pub fn with_restriction(self, restriction: Restriction) -> Position {
+        // Returns the same position played under `restriction`, e.g. renju
+        Position { restriction, ..self }
+    }
+
+    pub fn with_gravity(self, gravity: bool) -> Position {
+        // Returns the same position with pieces dropping to the lowest open square of their column, or not
+        Position { gravity, ..self }
+    }
+
+    pub(crate) fn from_parts(width: usize, height: usize, layers: usize, win_length: usize,
+                             pieces: [Bitboard; 2], to_move: usize) -> Position {
+        // Builds a position from a game's pieces and turn without checking them
+        Position { pieces, to_move, ..Position::layered(width, height, layers, win_length) }
+    }
+
+    pub fn width(&self) -> usize {
+        // Returns the number of columns on the board
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        // Returns the number of rows in each layer of the board
+        self.height
+    }
+
+    pub fn layers(&self) -> usize {
+        // Returns the number of layers stacked into the board, 1 for a flat board
+        self.layers
+    }
+
+    pub fn win_length(&self) -> usize {
+        // Returns the number of pieces in a row needed to win
+        self.win_length
+    }
+
+    pub fn rules(&self) -> Rules {
+        // Returns whether completing a line wins or loses
+        self.rules
+    }
+
+    pub fn restriction(&self) -> Restriction {
+        // Returns whether overlines count and which moves are fouls for player 1
+        self.restriction
+    }
+
+    pub fn gravity(&self) -> bool {
+        // Returns True if pieces drop to the lowest open square of their column
+        self.gravity
+    }
+
+    pub fn total_squares(&self) -> usize {
+        // Returns the number of locations (indices) on the board
+        self.width * self.height * self.layers
+    }
+
+    pub fn to_move(&self) -> usize {
+        // Returns the player whose turn it is
+        self.to_move
+    }
+
+    pub fn piece_at(&self, loc: usize) -> Option<usize> {
+        // Returns the player with a piece on the location (index), if any
+        (0..2).find(|&player| self.pieces[player].contains(loc))
+    }
+
+    pub fn is_legal(&self, loc: usize) -> bool {
+        // Returns True if the player to move may place a piece on the location (index)
+        self.can_play(loc) && self.outcome() == Outcome::InProgress
+    }
+
+    fn can_play(&self, loc: usize) -> bool {
+        // Returns True if the location (index) is on the board, open, supported and no foul for the player
+        // to move, without checking whether the game is already over
+        loc < self.total_squares() && self.piece_at(loc).is_none() && self.is_supported(loc)
+            && !self.is_foul(loc, self.to_move)
+    }
+
+    pub fn is_supported(&self, loc: usize) -> bool {
+        // Returns True unless gravity is on and the square below the location, in its layer, is open
+        !self.gravity || loc / self.width % self.height + 1 == self.height || self.piece_at(loc + self.width).is_some()
+    }
+
+    pub fn square_above(&self, loc: usize) -> Option<usize> {
+        // Under gravity, returns the square that a piece on the location (index) holds up, directly above it
+        // in its layer, or None on the top row or without gravity
+        match self.gravity && !(loc / self.width).is_multiple_of(self.height) {
+            true => Some(loc - self.width),
+            false => None,
+        }
+    }
+
+    pub fn is_foul(&self, loc: usize, player: usize) -> bool {
+        // Returns True if renju rules forbid `player`'s piece on the open location (index)
+        self.restriction.fouls(player)
+            && Renju::new(self.width, self.height, self.layers, self.win_length).is_foul(loc, |square| self.piece_at(square))
+    }
+
+    pub fn legal_moves(&self) -> Vec<usize> {
+        // Returns every location (index) where the player to move may place a piece,
+        // checking once that the game is still in play rather than once per square
+        if self.outcome() != Outcome::InProgress {
+            return vec![];
+        }
+        self.playable_moves()
+    }
+
+    pub fn playable_moves(&self) -> Vec<usize> {
+        // Returns every location (index) where the player to move could place a piece if play goes on;
+        // unlike `legal_moves` it does not check for a completed line, so searches that check each move
+        // with `completes_line` skip scanning the whole board
+        (Bitboard::first(self.total_squares()) & !(self.pieces[0] | self.pieces[1])).iter()
+            .filter(|&loc| self.is_supported(loc) && !self.is_foul(loc, self.to_move))
+            .collect()
+    }
+
+    pub fn is_full(&self) -> bool {
+        // Returns True if every location (index) holds a piece
+        (self.pieces[0] | self.pieces[1]).count() == self.total_squares()
+    }
+
+    pub fn try_apply(&self, loc: usize) -> Result<Position, MoveError> {
+        // Returns the position after the player to move places a piece on the location (index),
+        // or why the move is not allowed
+        if self.outcome() != Outcome::InProgress {
+            return Err(MoveError::GameOver);
+        }
+        if loc >= self.total_squares() {
+            return Err(MoveError::OutOfRange(loc));
+        }
+        if self.piece_at(loc).is_some() {
+            return Err(MoveError::Occupied(loc));
+        }
+        if !self.is_supported(loc) {
+            return Err(MoveError::Unsupported(loc));
+        }
+        if self.is_foul(loc, self.to_move) {
+            return Err(MoveError::Forbidden(loc));
+        }
+        Ok(self.with_move(loc))
+    }
+
+    pub(crate) fn with_move(&self, loc: usize) -> Position {
+        // Returns the position after the player to move places a piece on the location (index), see `with_piece`
+        self.with_piece(loc, self.to_move)
+    }
+
+    pub(crate) fn with_piece(&self, loc: usize, player: usize) -> Position {
+        // Returns the position after `player` places a piece on the location (index) and hands the turn
+        // to the other player, without checking the move; searches use it for moves they know are legal
+        let mut next = *self;
+        next.pieces[player] = self.pieces[player].with(loc);
+        next.to_move = 1 - player;
+        next
+    }
+
+    pub(crate) fn without_piece(&self, loc: usize, player: usize) -> Position {
+        // Returns the position with `player`'s piece taken back off the location (index) and the turn
+        // handed back to them
+        let mut next = *self;
+        next.pieces[player] = self.pieces[player].without(loc);
+        next.to_move = player;
+        next
+    }
+
+    pub fn apply(&self, loc: usize) -> Position {
+        // Returns the position after the player to move places a piece on the location (index);
+        // panics if the move is not legal, see `try_apply`
+        self.try_apply(loc).unwrap_or_else(|error| panic!("illegal move: {}", error))
+    }
+
+    pub fn transform(&self, symmetry: Symmetry) -> Position {
+        // Returns the position with every piece moved by the symmetry; panics if the symmetry
+        // does not fit the board, see `Symmetry::fits`
+        assert!(symmetry.fits(self.width, self.height, self.layers), "{:?} does not fit a {} board",
+                symmetry, board_name(self.width, self.height, self.layers));
+        let rows = self.height * self.layers;
+        let mut next = *self;
+        for player in 0..2 {
+            next.pieces[player] = self.pieces[player].iter()
+                .fold(Bitboard::EMPTY, |board, loc| board.with(symmetry.map(loc, self.width, rows)));
+        }
+        next
+    }
+
+    pub fn canonical(&self) -> (Position, Symmetry) {
+        // Returns the same representative for every position that is a rotation or reflection of this one,
+        // and the symmetry that maps this position onto it. Map a move in the canonical position back
+        // onto this one with `symmetry.inverse().map(loc, width, height * layers)`. Under gravity only
+        // the upright symmetries apply
+        symmetries(self.width, self.height, self.layers).into_iter()
+            .filter(|symmetry| !self.gravity || symmetry.is_upright())
+            .map(|symmetry| (self.transform(symmetry), symmetry))
+            .min_by_key(|(position, _)| position.pieces)
+            .expect("every board has the identity symmetry")
+    }
+
+    pub fn outcome(&self) -> Outcome {
+        // Returns whether someone has completed a line, and so won or lost by the rules, the board is full,
+        // or play goes on.
+        // For each direction, shifting a player's pieces back by one step at a time and masking
+        // leaves only the starts of complete lines, less any that run on into an overline when the
+        // restriction does not count them. Lines are reported in `line_mapping` order.
+        let span = self.win_length as isize - 1;
+        for &(layer_step, row_step, col_step) in &DIRECTIONS {
+            let starts = self.line_starts(layer_step, row_step, col_step);
+            if starts.is_empty() {
+                continue;
+            }
+            let step = ((layer_step * self.height as isize + row_step) * self.width as isize + col_step) as usize;
+            let wins = [0, 1].map(|player| {
+                (1..=span as usize).fold(starts & self.pieces[player],
+                                         |runs, length| runs & (self.pieces[player] >> (length * step)))
+            });
+            let direction = (layer_step, row_step, col_step);
+            let first = (0..2)
+                .filter_map(|player| {
+                    wins[player].iter().find(|&start| !self.is_overline(player, start, direction)).map(|start| (start, player))
+                })
+                .min();
+            if let Some((start, player)) = first {
+                let line = (0..=span as usize).map(|length| start + length * step).collect();
+                return Outcome::Win { player: self.rules.line_winner(player), line };
+            }
+        }
+        match self.is_full() {
+            true => Outcome::Draw,
+            false => Outcome::InProgress,
+        }
+    }
+
+    pub fn completes_line(&self, loc: usize) -> bool {
+        // Returns True if the piece on the location (index) is part of a completed line, see `line_at`
+        self.line_at(loc).is_some()
+    }
+
+    pub fn line_at(&self, loc: usize) -> Option<Vec<usize>> {
+        // Returns the line completed through the location (index) by the piece on it, if any, as `outcome`
+        // reports lines: the first in `line_mapping` order, and only exactly `win_length` in a row
+        // when the restriction does not count overlines for the piece's owner
+        let player = self.piece_at(loc)?;
+        let span = self.win_length as isize - 1;
+        let owns = |square: Option<usize>| square.is_some_and(|square| self.pieces[player].contains(square));
+        DIRECTIONS.iter()
+            .filter(|&&(layer_step, _, _)| layer_step == 0 || self.layers > 1)
+            .find_map(|&direction| {
+                // count the player's pieces running back and forward from the location
+                let reach = |sign: isize| (1..).take_while(|&steps| owns(self.step(loc, direction, sign * steps))).count();
+                let (back, forward) = (reach(-1) as isize, reach(1) as isize);
+                let length = back + forward + 1;
+                if length <= span || (length > span + 1 && self.restriction.exact(player)) {
+                    return None;
+                }
+                let start = -back.min(span);
+                Some((start..=start + span).filter_map(|steps| self.step(loc, direction, steps)).collect())
+            })
+    }
+
+    fn is_overline(&self, player: usize, start: usize, direction: (isize, isize, isize)) -> bool {
+        // Returns True if the restriction does not count overlines for `player` and the player's line from
+        // `start` in the direction has another of their pieces just before or after it
+        self.restriction.exact(player)
+            && [-1, self.win_length as isize].iter()
+                .filter_map(|&steps| self.step(start, direction, steps))
+                .any(|square| self.pieces[player].contains(square))
+    }
+
+    fn step(&self, loc: usize, direction: (isize, isize, isize), steps: isize) -> Option<usize> {
+        // Returns the location (index) `steps` along the direction from `loc`, or None if it is off the board
+        let (width, height, layers) = (self.width as isize, self.height as isize, self.layers as isize);
+        let (layer, row, col) = ((loc / (self.width * self.height)) as isize,
+                                 (loc / self.width % self.height) as isize, (loc % self.width) as isize);
+        let (l, r, c) = (layer + direction.0 * steps, row + direction.1 * steps, col + direction.2 * steps);
+        match (0..layers).contains(&l) && (0..height).contains(&r) && (0..width).contains(&c) {
+            true => Some(((l * height + r) * width + c) as usize),
+            false => None,
+        }
+    }
+
+    fn line_starts(&self, layer_step: isize, row_step: isize, col_step: isize) -> Bitboard {
+        // Returns the locations (indices) where a line in the direction fits on the board
+        let span = self.win_length as isize - 1;
+        let fits = |start: isize, step: isize, size: usize| (0..size as isize).contains(&(start + step * span));
+        let mut starts = Bitboard::EMPTY;
+        for layer in 0..self.layers {
+            for row in 0..self.height {
+                for col in 0..self.width {
+                    if fits(layer as isize, layer_step, self.layers) && fits(row as isize, row_step, self.height)
+                        && fits(col as isize, col_step, self.width) {
+                        starts = starts.with((layer * self.height + row) * self.width + col);
+                    }
+                }
+            }
+        }
+        starts
+    }
+}
+
+
+/***********
+ UNIT TESTS
+************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use crate::Game;
+
+    fn play(position: Position, moves: &[usize]) -> Position {
+        // Applies the given locations in order
+        moves.iter().fold(position, |position, &loc| position.apply(loc))
+    }
+
+    #[test]
+    fn test_apply_leaves_original() {
+        // Applying a move returns a new position and leaves the old one untouched
+        let empty = Position::new(3, 3, 3);
+        let next = empty.apply(4);
+        assert_eq!(empty.piece_at(4), None);
+        assert_eq!(empty.to_move(), 0);
+        assert_eq!(next.piece_at(4), Some(0));
+        assert_eq!(next.to_move(), 1);
+        assert_eq!(next.legal_moves(), vec![0, 1, 2, 3, 5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn test_outcomes() {
+        // Wins report the completed line, full boards without a line are draws
+        let empty = Position::new(3, 3, 3);
+        assert_eq!(empty.outcome(), Outcome::InProgress);
+        assert_eq!(play(empty, &[0, 3, 1, 4, 2]).outcome(), Outcome::Win { player: 0, line: vec![0, 1, 2] });
+        assert_eq!(play(empty, &[0, 4, 8, 2, 6, 3, 5, 7, 1]).outcome(), Outcome::Draw);
+        assert_eq!(play(empty, &[0, 3, 1, 4, 2]).legal_moves(), vec![]);
+        assert!(!play(empty, &[0, 3, 1, 4, 2]).is_legal(5));
+        assert!(play(empty, &[0, 3, 1, 4]).is_legal(5));
+    }
+
+    #[test]
+    fn test_misere_outcome() {
+        // Under misère rules the player who completes a line loses
+        let position = Position::new(3, 3, 3).with_rules(Rules::Misere);
+        assert_eq!(play(position, &[0, 3, 1, 4, 2]).outcome(), Outcome::Win { player: 1, line: vec![0, 1, 2] });
+        assert_eq!(play(position, &[0, 3, 1, 4, 2]).rules(), Rules::Misere);
+        assert_ne!(position, Position::new(3, 3, 3));
+    }
+
+    #[test]
+    fn test_exact_outcome() {
+        // When overlines do not count, a run longer than the win length completes no line
+        let position = Position::new(6, 2, 3).with_restriction(Restriction::Exact);
+        assert_eq!(play(position, &[0, 6, 1, 9, 3, 11, 2]).outcome(), Outcome::InProgress);
+        assert_eq!(play(position, &[0, 6, 1, 9, 2]).outcome(), Outcome::Win { player: 0, line: vec![0, 1, 2] });
+        assert_eq!(play(position, &[0, 6, 1, 9, 3, 11, 2]).restriction(), Restriction::Exact);
+    }
+
+    #[test]
+    fn test_line_at() {
+        // The line through a location is found from its piece, and overlines only count when allowed
+        let won = play(Position::new(3, 3, 3), &[0, 3, 1, 4, 2]);
+        assert_eq!(won.line_at(2), Some(vec![0, 1, 2]));
+        assert!(!won.completes_line(3));
+        assert!(!won.completes_line(5));
+        let overline = [0, 6, 1, 9, 3, 11, 2];
+        assert_eq!(play(Position::new(6, 2, 3), &overline).line_at(2), Some(vec![0, 1, 2]));
+        assert!(!play(Position::new(6, 2, 3).with_restriction(Restriction::Exact), &overline).completes_line(2));
+    }
+
+    #[test]
+    fn test_gravity_squares() {
+        // Under gravity a piece holds up the square above it, and the top row holds up nothing
+        let position = Position::new(3, 3, 3).with_gravity(true);
+        assert_eq!(position.playable_moves(), vec![6, 7, 8]);
+        assert_eq!(position.square_above(7), Some(4));
+        assert_eq!(position.square_above(1), None);
+        assert_eq!(Position::new(3, 3, 3).square_above(7), None);
+    }
+
+    #[test]
+    fn test_try_apply_errors() {
+        // Illegal moves are rejected with the same errors as the live game
+        let won = play(Position::new(3, 3, 3), &[0, 3, 1, 4, 2]);
+        assert_eq!(won.try_apply(5), Err(MoveError::GameOver));
+        let position = Position::new(3, 3, 3).apply(4);
+        assert_eq!(position.try_apply(4), Err(MoveError::Occupied(4)));
+        assert_eq!(position.try_apply(9), Err(MoveError::OutOfRange(9)));
+    }
+
+    #[test]
+    fn test_matches_game() {
+        // The position of a game agrees with the game's rules on a larger board
+        let mut game = Game::with_size(5, 4, 3);
+        game.set_seed(5);
+        while !game.end_game {
+            let position = game.position();
+            assert_eq!(position.legal_moves(), game.legal_moves());
+            let loc = game.random_move().unwrap();
+            game.place(loc).unwrap();
+            assert_eq!(position.apply(loc), game.position());
+            assert_eq!(game.position().outcome(), game.outcome);
+        }
+    }
+
+    #[test]
+    fn test_layered_matches_game() {
+        // On a layered board the position finds the same lines through the layers as the game
+        for &(size, seed) in &[(3, 1), (4, 2), (4, 3)] {
+            let mut game = Game::with_layers(size, size, size, size);
+            game.set_seed(seed);
+            while !game.end_game {
+                let loc = game.random_move().unwrap();
+                game.place(loc).unwrap();
+                assert_eq!(game.position().outcome(), game.outcome);
+            }
+        }
+        let position = play(Position::layered(3, 3, 3, 3), &[6, 0, 13, 1]);
+        assert_eq!(play(position, &[20]).outcome(), Outcome::Win { player: 0, line: vec![6, 13, 20] });
+        assert_eq!(position.total_squares(), 27);
+        // the half turn reverses the layers along with the rows and columns
+        assert_eq!(position.canonical().0, play(Position::layered(3, 3, 3, 3), &[20, 26, 13, 25]).canonical().0);
+    }
+
+    #[test]
+    fn test_canonical() {
+        // Rotations and reflections share one canonical form, reached by the returned symmetry
+        let position = Position::new(3, 3, 3).apply(0).apply(5);
+        let (canonical, symmetry) = position.canonical();
+        assert_eq!(position.transform(symmetry), canonical);
+        for &image in &Symmetry::ALL {
+            assert_eq!(position.transform(image).canonical().0, canonical);
+        }
+        let openings: HashSet<Position> = (0..9).map(|loc| Position::new(3, 3, 3).apply(loc).canonical().0).collect();
+        assert_eq!(openings.len(), 3);  // corner, edge and center
+        assert_eq!(Position::new(4, 3, 3).apply(0).canonical().0, Position::new(4, 3, 3).apply(11).canonical().0);
+    }
+
+    #[test]
+    fn test_distinct_positions_up_to_symmetry() {
+        // The 5,478 reachable positions of the standard game fall into 765 symmetry classes
+        let mut seen = HashSet::new();
+        let mut frontier = vec![Position::new(3, 3, 3)];
+        while let Some(position) = frontier.pop() {
+            if seen.insert(position) {
+                frontier.extend(position.legal_moves().into_iter().map(|loc| position.apply(loc)));
+            }
+        }
+        assert_eq!(seen.len(), 5_478);
+        assert_eq!(seen.iter().map(|position| position.canonical().0).collect::<HashSet<Position>>().len(), 765);
+    }
+
+    #[test]
+    fn test_largest_board() {
+        // Locations in the last word of the bitmask are stored and read back
+        let position = Position::new(16, 16, 5).apply(255);
+        assert_eq!(position.piece_at(255), Some(0));
+        assert_eq!(position.legal_moves().len(), 255);
+    }
+}
@@ -0,0 +1,161 @@
+/*
+*  Renju fouls. Under renju rules player 1 may not make an overline (more than `win_length` in a row),
+*  two fours at once or two open threes at once, unless the same move makes exactly `win_length` in a
+*  row, which wins. Each direction through the move counts as at most one four or one three, and threes
+*  are not checked for whether their own completion would be a foul, as tournament renju does.
+*/
+
+use crate::DIRECTIONS;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cell {
+    // A square along one direction through the move, from player 1's point of view
+    Own,        // holds player 1's piece, or is the move itself
+    Open,       // empty
+    Blocked,    // holds player 2's piece or is off the board
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Renju {
+    // Board geometry needed to follow the runs through a move
+    width: usize,       // number of columns on the board
+    height: usize,      // number of rows in each layer of the board
+    layers: usize,      // number of layers stacked into the board, 1 for a flat board
+    win_length: usize,  // number of pieces in a row needed to win
+}
+
+impl Renju {
+    pub fn new(width: usize, height: usize, layers: usize, win_length: usize) -> Renju {
+        // Checks moves on a board of `layers` stacked `width` x `height` layers won by `win_length` in a row
+        Renju { width, height, layers, win_length }
+    }
+
+    pub fn is_foul(&self, loc: usize, piece_at: impl Fn(usize) -> Option<usize>) -> bool {
+        // Returns True if player 1 may not place a piece on the open location (index), given the
+        // player with a piece on each location
+        let runs: Vec<Vec<Cell>> = DIRECTIONS.iter()
+            .filter(|&&(layer_step, _, _)| layer_step == 0 || self.layers > 1)
+            .map(|&direction| self.cells_along(loc, direction, &piece_at))
+            .collect();
+        let centre = self.win_length + 1;
+        let longest = runs.iter().map(|cells| run_through(cells, centre).len()).max().unwrap_or(0);
+        if runs.iter().any(|cells| run_through(cells, centre).len() == self.win_length) {
+            return false;
+        }
+        if longest > self.win_length {
+            return true;
+        }
+        let fours = runs.iter().filter(|cells| self.is_four(cells)).count();
+        let threes = runs.iter().filter(|cells| !self.is_four(cells) && self.is_open_three(cells)).count();
+        fours > 1 || threes > 1
+    }
+
+    fn cells_along(&self, loc: usize, direction: (isize, isize, isize),
+                   piece_at: &impl Fn(usize) -> Option<usize>) -> Vec<Cell> {
+        // Lists the squares up to `win_length` + 1 steps either side of the move, which sits in the middle
+        let (width, height, layers) = (self.width as isize, self.height as isize, self.layers as isize);
+        let (layer, row, col) = ((loc / (self.width * self.height)) as isize,
+                                 (loc / self.width % self.height) as isize, (loc % self.width) as isize);
+        let reach = self.win_length as isize + 1;
+        (-reach..=reach)
+            .map(|step| {
+                let (l, r, c) = (layer + direction.0 * step, row + direction.1 * step, col + direction.2 * step);
+                if !(0..layers).contains(&l) || !(0..height).contains(&r) || !(0..width).contains(&c) {
+                    return Cell::Blocked;
+                }
+                match piece_at(((l * height + r) * width + c) as usize) {
+                    _ if step == 0 => Cell::Own,
+                    Some(0) => Cell::Own,
+                    Some(_) => Cell::Blocked,
+                    None => Cell::Open,
+                }
+            })
+            .collect()
+    }
+
+    fn is_four(&self, cells: &[Cell]) -> bool {
+        // Returns True if one more piece in this direction would make exactly `win_length` in a row
+        let centre = self.win_length + 1;
+        open_squares(cells).any(|square| {
+            let cells = with_own(cells, square);
+            run_through(&cells, centre).len() == self.win_length
+        })
+    }
+
+    fn is_open_three(&self, cells: &[Cell]) -> bool {
+        // Returns True if one more piece in this direction would make an open four: `win_length` - 1
+        // in a row with an open square at each end, either of which makes exactly `win_length`
+        let centre = self.win_length + 1;
+        open_squares(cells).any(|square| {
+            let cells = with_own(cells, square);
+            let run = run_through(&cells, centre);
+            run.len() + 1 == self.win_length && run.contains(&square)
+                && cells[run.start - 1] == Cell::Open && cells[run.end] == Cell::Open
+                && cells[run.start - 2] != Cell::Own && cells[run.end + 1] != Cell::Own
+        })
+    }
+}
+
+fn open_squares(cells: &[Cell]) -> impl Iterator<Item = usize> + '_ {
+    // Returns the positions of the open squares along a direction
+    (0..cells.len()).filter(move |&square| cells[square] == Cell::Open)
+}
+
+fn with_own(cells: &[Cell], square: usize) -> Vec<Cell> {
+    // Returns the squares with player 1's piece added on `square`
+    let mut cells = cells.to_vec();
+    cells[square] = Cell::Own;
+    cells
+}
+
+fn run_through(cells: &[Cell], centre: usize) -> std::ops::Range<usize> {
+    // Returns the positions of the unbroken run of player 1's pieces through `centre`
+    let start = (0..centre).rev().find(|&square| cells[square] != Cell::Own).map_or(0, |square| square + 1);
+    let end = (centre..cells.len()).find(|&square| cells[square] != Cell::Own).unwrap_or(cells.len());
+    start..end
+}
+
+
+/***********
+ UNIT TESTS
+************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_foul(own: &[usize], other: &[usize], loc: usize) -> bool {
+        // Checks a move on the 15x15 renju board with the given pieces for each player
+        Renju::new(15, 15, 1, 5).is_foul(loc, |square| {
+            if own.contains(&square) { Some(0) } else if other.contains(&square) { Some(1) } else { None }
+        })
+    }
+
+    #[test]
+    fn test_overline() {
+        // Six in a row is a foul, but exactly five is not
+        assert!(is_foul(&[110, 111, 113, 114, 115], &[], 112));
+        assert!(!is_foul(&[110, 111, 113, 114], &[], 112));
+    }
+
+    #[test]
+    fn test_double_four() {
+        // Two fours at once are a foul, one four and one three are not
+        assert!(is_foul(&[109, 110, 111, 67, 82, 97], &[], 112));
+        assert!(!is_foul(&[109, 110, 111, 82, 97], &[], 112));
+    }
+
+    #[test]
+    fn test_double_three() {
+        // Two open threes at once are a foul, unless one of them is blocked at an end
+        assert!(is_foul(&[110, 111, 82, 97], &[], 112));
+        assert!(is_foul(&[109, 111, 82, 97], &[], 112));
+        assert!(!is_foul(&[110, 111, 82, 97], &[113], 112));
+        assert!(!is_foul(&[110, 111, 82, 97], &[109], 112));
+    }
+
+    #[test]
+    fn test_five_overrides_fouls() {
+        // A move that makes exactly five wins even if it also makes a double three
+        assert!(!is_foul(&[108, 109, 110, 111, 82, 97, 96, 128], &[], 112));
+    }
+}
@@ -0,0 +1,96 @@
+/*
+*  Seeded random number generator for everything random in a game, so that an automatic game
+*  can be replayed exactly by starting a new game from the same seed.
+*/
+
+use std::cell::RefCell;
+use std::fmt;
+use rand::rngs::StdRng;
+use rand::{thread_rng, Rng, RngCore, SeedableRng};
+
+
+pub struct SeededRng {
+    // Generator that remembers the seed it started from
+    seed: u64,                  // seed the generator was created with
+    rng: RefCell<StdRng>,       // generator state, shared so read-only game views can still draw numbers
+}
+
+impl SeededRng {
+    pub fn new(seed: u64) -> SeededRng {
+        // Starts a generator from `seed`
+        SeededRng { seed, rng: RefCell::new(StdRng::seed_from_u64(seed)) }
+    }
+
+    pub fn from_entropy() -> SeededRng {
+        // Starts a generator from a fresh random seed, which can still be read back with `seed`
+        SeededRng::new(thread_rng().gen())
+    }
+
+    pub fn seed(&self) -> u64 {
+        // Returns the seed the generator started from
+        self.seed
+    }
+
+    pub fn gen_range(&self, low: usize, high: usize) -> usize {
+        // Returns a number in `low..high`
+        self.rng.borrow_mut().gen_range(low, high)
+    }
+
+    pub fn gen_bool(&self, probability: f64) -> bool {
+        // Returns True with the given probability
+        self.rng.borrow_mut().gen_bool(probability)
+    }
+
+    pub fn next_u64(&self) -> u64 {
+        // Returns a random number, e.g. to seed a search's own generator
+        self.rng.borrow_mut().next_u64()
+    }
+}
+
+impl Default for SeededRng {
+    fn default() -> SeededRng {
+        // Games are seeded afresh unless a seed is chosen
+        SeededRng::from_entropy()
+    }
+}
+
+impl fmt::Debug for SeededRng {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        // Show the seed, which is all that is needed to replay a game
+        write!(formatter, "SeededRng {{ seed: {} }}", self.seed)
+    }
+}
+
+impl PartialEq for SeededRng {
+    fn eq(&self, other: &SeededRng) -> bool {
+        // Generators are alike when they started from the same seed
+        self.seed == other.seed
+    }
+}
+
+
+/***********
+ UNIT TESTS
+************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_numbers() {
+        // Two generators from one seed draw the same numbers
+        let first = SeededRng::new(7);
+        let second = SeededRng::new(7);
+        let draws = |rng: &SeededRng| -> Vec<usize> { (0..20).map(|_| rng.gen_range(0, 100)).collect() };
+        assert_eq!(draws(&first), draws(&second));
+        assert_eq!(first.seed(), 7);
+    }
+
+    #[test]
+    fn test_fresh_seed_is_recorded() {
+        // A generator seeded from entropy can be recreated from its seed
+        let original = SeededRng::from_entropy();
+        let replay = SeededRng::new(original.seed());
+        assert_eq!(original.next_u64(), replay.next_u64());
+    }
+}
@@ -0,0 +1,745 @@
+/*
+*  Alpha-beta search for boards too large for plain minimax. The searcher adds a Zobrist-hashed
+*  transposition table keyed on the position's canonical form under the board's symmetries,
+*  move ordering by line potential, and iterative deepening under a node or time budget.
+*/
+
+use std::collections::HashMap;
+use std::time::Duration;
+use crate::symmetry::{permutations, symmetries};
+use crate::timer::Timer;
+use crate::position::Position;
+use crate::{Game, Rules};
+
+const WIN_SCORE: i32 = 1_000_000_000;   // score for winning now, reduced by one per ply until the win
+const MATE_BOUND: i32 = WIN_SCORE - 10_000; // scores beyond this are forced wins or losses
+const MAX_WEIGHT_EXP: usize = 8;        // line weights stop growing past 4^8 to keep evaluations in range
+const CHECK_INTERVAL: u64 = 1024;       // nodes searched between budget checks
+const ZOBRIST_SEED: u64 = 0x2545_f491_4f6c_dd1d;    // fixed seed so hashes are reproducible
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SearchLimits {
+    // Budget for one search; `None` means unlimited
+    pub max_nodes: Option<u64>,         // stop after visiting this many positions
+    pub max_time: Option<Duration>,     // stop once this much time has passed
+    pub max_depth: Option<usize>,       // stop deepening after this many plies
+}
+
+impl SearchLimits {
+    pub fn unlimited() -> SearchLimits {
+        // Search until the position is solved
+        SearchLimits::default()
+    }
+
+    pub fn nodes(max_nodes: u64) -> SearchLimits {
+        // Search at most `max_nodes` positions
+        SearchLimits { max_nodes: Some(max_nodes), ..SearchLimits::default() }
+    }
+
+    pub fn time(max_time: Duration) -> SearchLimits {
+        // Search for at most `max_time`
+        SearchLimits { max_time: Some(max_time), ..SearchLimits::default() }
+    }
+
+    pub fn depth(self, max_depth: usize) -> SearchLimits {
+        // Also stop deepening after `max_depth` plies
+        SearchLimits { max_depth: Some(max_depth), ..self }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchResult {
+    // Best move found by a search and how much it can be trusted
+    pub best_move: Option<usize>,   // location (index) to play, None if the game is over
+    pub score: i32,                 // value for the player to move: positive is good, 0 is a draw
+    pub depth: usize,               // deepest fully searched iteration, in plies
+    pub nodes: u64,                 // positions visited
+    pub solved: bool,               // True if `score` is the exact game-theoretic value
+}
+
+impl SearchResult {
+    pub fn is_win(&self) -> bool {
+        // Returns True if the player to move can force a win
+        self.solved && self.score > MATE_BOUND
+    }
+
+    pub fn is_loss(&self) -> bool {
+        // Returns True if the player to move loses against perfect play
+        self.solved && self.score < -MATE_BOUND
+    }
+
+    pub fn is_draw(&self) -> bool {
+        // Returns True if perfect play from both sides draws
+        self.solved && self.score == 0
+    }
+
+    pub fn distance(&self) -> Option<usize> {
+        // Returns the number of moves until a forced win or loss, counting the first move searched
+        match self.is_win() || self.is_loss() {
+            true => Some((WIN_SCORE - self.score.abs()) as usize),
+            false => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Bound {
+    // How a stored score relates to the true value of the position
+    Exact,
+    Lower,
+    Upper,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    // Transposition table record, with the best move stored in canonical orientation
+    depth: usize,
+    score: i32,
+    bound: Bound,
+    best_move: Option<usize>,
+}
+
+pub struct AlphaBeta {
+    // Searcher for one board geometry; it can be reused for many positions on that board
+    win_length: usize,                  // pieces in a row needed to win
+    rules: Rules,                       // whether completing a line wins or loses
+    lines: Vec<Vec<usize>>,             // locations of every winning line
+    lines_through: Vec<Vec<usize>>,     // indices of the winning lines through each location
+    symmetries: Vec<Vec<usize>>,        // location permutations that map the board onto itself
+    zobrist: Vec<[u64; 2]>,             // random key for each player's piece on each location
+    weights: Vec<i32>,                  // value of a live line holding n pieces of one player
+    table: HashMap<u64, Entry>,         // transposition table keyed by canonical hash
+
+    // state of the position being searched
+    position: Position,                 // pieces on the board, which also decides wins, gravity and fouls
+    line_counts: Vec<[usize; 2]>,       // pieces of each player on each line
+    dead_lines: usize,                  // lines holding pieces of both players, which nobody can win
+    empties: usize,                     // open squares left
+    hashes: Vec<u64>,                   // Zobrist hash of the position under each symmetry
+
+    // budget bookkeeping
+    nodes: u64,
+    limits: SearchLimits,
+    timer: Timer,
+    aborted: bool,
+}
+
+impl AlphaBeta {
+    pub fn new(game: &Game) -> AlphaBeta {
+        // Builds the symmetries and Zobrist keys for the game's board
+        let total_squares = game.total_squares();
+
+        let mut seed = ZOBRIST_SEED;
+        let zobrist = (0..total_squares).map(|_| [splitmix64(&mut seed), splitmix64(&mut seed)]).collect();
+        let weights = (0..=game.win_length).map(|count| 1 << (2 * count.min(MAX_WEIGHT_EXP))).collect();
+        let upright: Vec<_> = symmetries(game.width, game.height, game.layers).into_iter()
+            .filter(|symmetry| !game.gravity || symmetry.is_upright())
+            .collect();
+
+        AlphaBeta {
+            win_length: game.win_length,
+            rules: game.rules,
+            lines: game.lines.clone(),
+            lines_through: game.lines_through.clone(),
+            symmetries: permutations(&upright, game.width, game.height * game.layers),
+            zobrist,
+            weights,
+            table: HashMap::new(),
+            position: game.position(),
+            line_counts: vec![[0, 0]; game.lines.len()],
+            dead_lines: 0,
+            empties: total_squares,
+            hashes: vec![],
+            nodes: 0,
+            limits: SearchLimits::unlimited(),
+            timer: Timer::start(),
+            aborted: false,
+        }
+    }
+
+    pub fn search(&mut self, game: &Game, limits: SearchLimits) -> SearchResult {
+        // Finds the best move for the current player by iterative deepening until the
+        // position is solved or the budget runs out
+        self.load(game);
+        self.limits = limits;
+        self.timer = Timer::start();
+        self.nodes = 0;
+        self.aborted = false;
+
+        let mut result = SearchResult { best_move: None, score: 0, depth: 0, nodes: 0, solved: game.end_game };
+        if game.end_game {
+            return result;
+        }
+
+        let player = game.curr_player;
+        let max_depth = limits.max_depth.map_or(self.empties, |max_depth| max_depth.min(self.empties));
+        for depth in 1..=max_depth {
+            let (best_move, score) = self.search_root(depth, player, result.best_move);
+            if self.aborted {
+                // a partial iteration still improves on nothing at all
+                if result.best_move.is_none() {
+                    result.best_move = best_move;
+                    result.score = score;
+                }
+                break;
+            }
+            result.best_move = best_move;
+            result.score = score;
+            result.depth = depth;
+
+            // a full-depth search never stops on an estimate, and forced results cannot change
+            if depth == self.empties || score.abs() > MATE_BOUND {
+                result.solved = true;
+                break;
+            }
+        }
+        result.nodes = self.nodes;
+        result
+    }
+
+    pub fn evaluate_moves(&mut self, game: &Game, limits: SearchLimits) -> Vec<SearchResult> {
+        // Scores every legal move for the current player, in location (index) order, each with its own
+        // `limits`; a result's score is for the player making the move and its best move is that move
+        self.load(game);
+        if game.end_game {
+            return vec![];
+        }
+        let player = game.curr_player;
+        let moves = self.position.playable_moves();
+        moves.into_iter().map(|loc| self.evaluate_move(loc, player, limits)).collect()
+    }
+
+    fn evaluate_move(&mut self, loc: usize, player: usize, limits: SearchLimits) -> SearchResult {
+        // Deepens the search below one root move until its score is exact or the budget runs out
+        self.limits = limits;
+        self.timer = Timer::start();
+        self.nodes = 0;
+        self.aborted = false;
+
+        let mut result = SearchResult { best_move: Some(loc), score: 0, depth: 1, nodes: 0, solved: true };
+        if self.make(loc, player) {
+            result.score = self.line_score(1);
+        } else if self.empties > 0 {
+            result.solved = false;
+            let max_depth = limits.max_depth.map_or(self.empties, |max_depth| max_depth.min(self.empties));
+            for depth in 0..=max_depth {
+                let score = -self.negamax(depth, 1, -WIN_SCORE, WIN_SCORE, 1 - player);
+                if self.aborted {
+                    break;
+                }
+                result.score = score;
+                result.depth = depth + 1;
+                if depth == self.empties || score.abs() > MATE_BOUND {
+                    result.solved = true;
+                    break;
+                }
+            }
+        }
+        self.unmake(loc, player);
+        result.nodes = self.nodes;
+        result
+    }
+
+    fn load(&mut self, game: &Game) {
+        // Copies the game's pieces into the search state
+        self.position = game.position();
+        self.line_counts = vec![[0, 0]; self.lines.len()];
+        self.dead_lines = 0;
+        self.empties = game.total_squares();
+        self.hashes = vec![0; self.symmetries.len()];
+        for player in 0..2 {
+            for loc in game.pieces[player].iter() {
+                self.count_piece(loc, player);
+            }
+        }
+    }
+
+    fn search_root(&mut self, depth: usize, player: usize, first: Option<usize>) -> (Option<usize>, i32) {
+        // Searches every root move to `depth` plies, starting with the previous iteration's
+        // best move, and returns the best move and its score
+        let mut alpha = -WIN_SCORE;
+        let beta = WIN_SCORE;
+        let mut best: Option<(usize, i32)> = None;
+
+        for loc in self.ordered_moves(player, first) {
+            if self.is_foul(loc, player) {
+                continue;
+            }
+            let score = if self.make(loc, player) {
+                self.line_score(1)
+            } else {
+                -self.negamax(depth - 1, 1, -beta, -alpha, 1 - player)
+            };
+            self.unmake(loc, player);
+            if self.aborted {
+                break;
+            }
+            match best {
+                Some((_, best_score)) if best_score >= score => (),
+                _ => best = Some((loc, score)),
+            }
+            alpha = alpha.max(score);
+        }
+        match best {
+            Some((loc, score)) => (Some(loc), score),
+            None => (None, 0),
+        }
+    }
+
+    fn negamax(&mut self, depth: usize, ply: i32, mut alpha: i32, beta: i32, player: usize) -> i32 {
+        // Scores the position for `player`, who is about to move
+        self.nodes += 1;
+        if self.nodes.is_multiple_of(CHECK_INTERVAL) && self.out_of_budget() {
+            self.aborted = true;
+        }
+        if self.aborted {
+            return 0;
+        }
+        if self.empties == 0 || self.dead_lines == self.lines.len() {
+            return 0;
+        }
+
+        // an open line one piece short is an immediate win; under misère rules lines are to be avoided,
+        // so neither this nor the forced blocks below apply
+        let standard = self.rules == Rules::Standard;
+        if standard && self.threat_square(player).is_some() {
+            return WIN_SCORE - (ply + 1);
+        }
+        if depth == 0 {
+            return self.evaluate(player);
+        }
+
+        // probe the transposition table
+        let (key, transform) = self.canonical_key();
+        let original_alpha = alpha;
+        let mut table_move = None;
+        if let Some(entry) = self.table.get(&key) {
+            table_move = entry.best_move.map(|canonical| self.uncanonical_move(transform, canonical));
+            if entry.depth >= depth {
+                let score = from_table_score(entry.score, ply);
+                match entry.bound {
+                    Bound::Exact => return score,
+                    Bound::Lower if score >= beta => return score,
+                    Bound::Upper if score <= alpha => return score,
+                    _ => (),
+                }
+            }
+        }
+
+        // an opponent threat must be blocked, and two threats cannot both be blocked
+        let opponent = 1 - player;
+        let threats = if standard { self.threat_squares(opponent) } else { vec![] };
+        let moves = match threats {
+            ref threats if threats.len() > 1 => return -(WIN_SCORE - (ply + 2)),
+            ref threats if threats.len() == 1 => threats.clone(),
+            _ => self.ordered_moves(player, table_move),
+        };
+
+        let mut best_score = -WIN_SCORE;
+        let mut best_move = None;
+        for loc in moves {
+            if self.is_foul(loc, player) {
+                continue;
+            }
+            let score = if self.make(loc, player) {
+                self.line_score(ply + 1)
+            } else {
+                -self.negamax(depth - 1, ply + 1, -beta, -alpha, opponent)
+            };
+            self.unmake(loc, player);
+            if self.aborted {
+                return 0;
+            }
+            if score > best_score {
+                best_score = score;
+                best_move = Some(loc);
+            }
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        let bound = if best_score <= original_alpha {
+            Bound::Upper
+        } else if best_score >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        let best_move = best_move.map(|loc| self.symmetries[transform][loc]);
+        self.table.insert(key, Entry { depth, score: to_table_score(best_score, ply), bound, best_move });
+        best_score
+    }
+
+    fn line_score(&self, ply: i32) -> i32 {
+        // Scores completing a line on move `ply` for the player who completed it
+        match self.rules {
+            Rules::Standard => WIN_SCORE - ply,
+            Rules::Misere => -(WIN_SCORE - ply),
+        }
+    }
+
+    fn make(&mut self, loc: usize, player: usize) -> bool {
+        // Places `player`'s piece on `loc`, returning True if it completes a line
+        self.position = self.position.with_piece(loc, player);
+        self.count_piece(loc, player);
+        self.position.completes_line(loc)
+    }
+
+    fn count_piece(&mut self, loc: usize, player: usize) {
+        // Adds `player`'s piece on `loc` to the line counts and hashes
+        self.empties -= 1;
+        for &line in &self.lines_through[loc] {
+            let counts = &mut self.line_counts[line];
+            if counts[player] == 0 && counts[1 - player] > 0 {
+                self.dead_lines += 1;
+            }
+            counts[player] += 1;
+        }
+        for (hash, symmetry) in self.hashes.iter_mut().zip(&self.symmetries) {
+            *hash ^= self.zobrist[symmetry[loc]][player];
+        }
+    }
+
+    fn unmake(&mut self, loc: usize, player: usize) {
+        // Takes `player`'s piece back off `loc`
+        self.position = self.position.without_piece(loc, player);
+        self.empties += 1;
+        for &line in &self.lines_through[loc] {
+            let counts = &mut self.line_counts[line];
+            counts[player] -= 1;
+            if counts[player] == 0 && counts[1 - player] > 0 {
+                self.dead_lines -= 1;
+            }
+        }
+        for (hash, symmetry) in self.hashes.iter_mut().zip(&self.symmetries) {
+            *hash ^= self.zobrist[symmetry[loc]][player];
+        }
+    }
+
+    fn threat_squares(&self, player: usize) -> Vec<usize> {
+        // Returns the open squares that would complete a line for `player` and can be played now
+        let mut squares: Vec<usize> = vec![];
+        for loc in self.threat_candidates(player) {
+            if !squares.contains(&loc) {
+                squares.push(loc);
+            }
+        }
+        squares
+    }
+
+    fn threat_square(&self, player: usize) -> Option<usize> {
+        // Returns an open square that would complete a line for `player` and can be played now, if there is one
+        self.threat_candidates(player).next()
+    }
+
+    fn threat_candidates<'a>(&'a self, player: usize) -> impl Iterator<Item = usize> + 'a {
+        // Yields, line by line, the open square of each line one piece short for `player` that can be played
+        // now and completes a line there, as the position judges overlines
+        self.lines.iter().zip(&self.line_counts)
+            .filter(move |(_, counts)| counts[player] + 1 == self.win_length && counts[1 - player] == 0)
+            .filter_map(move |(line, _)| line.iter().copied().find(|&loc| self.position.piece_at(loc).is_none()))
+            .filter(move |&loc| {
+                self.position.is_supported(loc) && self.position.with_piece(loc, player).completes_line(loc)
+            })
+    }
+
+    fn is_playable(&self, loc: usize) -> bool {
+        // Returns True if the location is open and, under gravity, rests on a piece or the bottom row
+        self.position.piece_at(loc).is_none() && self.position.is_supported(loc)
+    }
+
+    fn is_foul(&self, loc: usize, player: usize) -> bool {
+        // Returns True if renju rules forbid `player` from placing a piece on the open location
+        self.position.is_foul(loc, player)
+    }
+
+    fn ordered_moves(&self, player: usize, first: Option<usize>) -> Vec<usize> {
+        // Returns the open squares, skipping moves that are symmetric to one already listed,
+        // with `first` (the transposition table's move) ahead of squares on the most promising lines
+        let stabilizer: Vec<&Vec<usize>> = self.symmetries.iter().zip(&self.hashes)
+            .filter(|&(_, &hash)| hash == self.hashes[0])
+            .map(|(symmetry, _)| symmetry)
+            .collect();
+
+        let mut moves: Vec<(usize, i32)> = (0..self.position.total_squares())
+            .filter(|&loc| self.is_playable(loc))
+            .filter(|&loc| stabilizer.iter().all(|symmetry| symmetry[loc] >= loc))
+            .map(|loc| (loc, self.square_value(loc, player)))
+            .collect();
+        moves.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        let mut ordered: Vec<usize> = moves.into_iter().map(|(loc, _)| loc).collect();
+        if let Some(first) = first {
+            if let Some(position) = ordered.iter().position(|&loc| loc == first) {
+                ordered.remove(position);
+                ordered.insert(0, first);
+            }
+        }
+        ordered
+    }
+
+    fn square_value(&self, loc: usize, player: usize) -> i32 {
+        // Scores a square by the live lines through it for both players; under misère rules
+        // the quietest squares score highest
+        self.lines_through[loc].iter()
+            .map(|&line| {
+                let counts = self.line_counts[line];
+                let mine = if counts[1 - player] == 0 { self.weights[counts[player]] } else { 0 };
+                let theirs = if counts[player] == 0 { self.weights[counts[1 - player]] } else { 0 };
+                mine + theirs
+            })
+            .sum::<i32>() * self.rules_sign()
+    }
+
+    fn evaluate(&self, player: usize) -> i32 {
+        // Estimates the position for `player` by comparing both players' live lines
+        self.line_counts.iter()
+            .map(|counts| match (counts[player], counts[1 - player]) {
+                (0, 0) => 0,
+                (mine, 0) => self.weights[mine],
+                (0, theirs) => -self.weights[theirs],
+                _ => 0,
+            })
+            .sum::<i32>() * self.rules_sign()
+    }
+
+    fn rules_sign(&self) -> i32 {
+        // Live lines are worth having under standard rules and a liability under misère rules
+        match self.rules {
+            Rules::Standard => 1,
+            Rules::Misere => -1,
+        }
+    }
+
+    fn canonical_key(&self) -> (u64, usize) {
+        // Returns the smallest hash over the board's symmetries and the symmetry that produced it
+        self.hashes.iter().enumerate()
+            .map(|(transform, &hash)| (hash, transform))
+            .min()
+            .unwrap_or((0, 0))
+    }
+
+    fn uncanonical_move(&self, transform: usize, canonical: usize) -> usize {
+        // Maps a location in canonical orientation back onto the current board
+        self.symmetries[transform].iter().position(|&loc| loc == canonical).unwrap_or(canonical)
+    }
+
+    fn out_of_budget(&self) -> bool {
+        // Returns True once the node or time budget has been used up
+        self.limits.max_nodes.is_some_and(|max_nodes| self.nodes >= max_nodes)
+            || self.limits.max_time.is_some_and(|max_time| self.timer.elapsed() >= max_time)
+    }
+}
+
+pub fn alpha_beta_move(game: &Game, limits: SearchLimits) -> Option<usize> {
+    // Returns the best location (index) for the current player found within `limits`
+    AlphaBeta::new(game).search(game, limits).best_move
+}
+
+fn to_table_score(score: i32, ply: i32) -> i32 {
+    // Stores forced results as distance from this position rather than from the root
+    if score > MATE_BOUND { score + ply } else if score < -MATE_BOUND { score - ply } else { score }
+}
+
+fn from_table_score(score: i32, ply: i32) -> i32 {
+    // Converts a stored forced result back to distance from the root
+    if score > MATE_BOUND { score - ply } else if score < -MATE_BOUND { score + ply } else { score }
+}
+
+fn splitmix64(state: &mut u64) -> u64 {
+    // Small, fast generator for Zobrist keys
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+
+/***********
+ UNIT TESTS
+************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::{AiPlayer, Player};
+    use crate::{Difficulty, Restriction, Strategy};
+
+    fn play(game: &mut Game, moves: &[usize]) {
+        // Plays the given locations in order
+        for &loc in moves {
+            game.place(loc).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_evaluate_moves() {
+        // Each root move gets its own exact score, with the distance to a forced result
+        let mut game = Game::new();
+        play(&mut game, &[0, 3, 1, 4]);
+        let results = AlphaBeta::new(&game).evaluate_moves(&game, SearchLimits::unlimited());
+        assert_eq!(results.iter().map(|result| result.best_move.unwrap()).collect::<Vec<usize>>(), vec![2, 5, 6, 7, 8]);
+        assert!(results.iter().all(|result| result.solved));
+        assert_eq!((results[0].is_win(), results[0].distance()), (true, Some(1)));
+        assert_eq!((results[2].is_loss(), results[2].distance()), (true, Some(2)));
+        assert!(results[1].is_draw());
+    }
+
+    #[test]
+    fn test_misere_matches_solver() {
+        // Under misère rules the search agrees with the exhaustive solver after each opening move
+        for loc in 0..9 {
+            let mut game = Game::new();
+            game.set_rules(Rules::Misere);
+            play(&mut game, &[loc]);
+            let result = AlphaBeta::new(&game).search(&game, SearchLimits::unlimited());
+            let expected = match crate::solve::solve(&game.position()).value {
+                crate::solve::Value::Win => (true, false, false),
+                crate::solve::Value::Draw => (false, true, false),
+                crate::solve::Value::Loss => (false, false, true),
+            };
+            assert_eq!((result.is_win(), result.is_draw(), result.is_loss()), expected);
+        }
+    }
+
+    #[test]
+    fn test_solves_standard_board() {
+        // The empty 3x3 board is a draw
+        let game = Game::new();
+        let result = AlphaBeta::new(&game).search(&game, SearchLimits::unlimited());
+        assert!(result.is_draw());
+        assert!(result.best_move.is_some());
+    }
+
+    #[test]
+    fn test_takes_win_and_blocks() {
+        // X completes the top row, and O blocks it one move earlier
+        let mut game = Game::new();
+        play(&mut game, &[0, 3, 1, 4]);
+        let result = AlphaBeta::new(&game).search(&game, SearchLimits::unlimited());
+        assert_eq!(result.best_move, Some(2));
+        assert!(result.is_win());
+
+        let mut game = Game::new();
+        play(&mut game, &[0, 4, 1]);
+        assert_eq!(alpha_beta_move(&game, SearchLimits::unlimited()), Some(2));
+    }
+
+    #[test]
+    fn test_finds_forced_win() {
+        // Three in a row on a 4x4 board is a first player win
+        let game = Game::with_size(4, 4, 3);
+        let result = AlphaBeta::new(&game).search(&game, SearchLimits::unlimited());
+        assert!(result.is_win());
+    }
+
+    #[test]
+    fn test_solves_four_by_four() {
+        // Four in a row on a 4x4 board is a draw with perfect play
+        let game = Game::with_size(4, 4, 4);
+        let result = AlphaBeta::new(&game).search(&game, SearchLimits::unlimited());
+        assert!(result.is_draw());
+    }
+
+    #[test]
+    fn test_blocks_line_through_layers() {
+        // In Qubic, O blocks three X pieces stacked through the layers, and X would take the square to win
+        let mut game = Game::with_layers(4, 4, 4, 4);
+        play(&mut game, &[5, 0, 21, 1, 37]);
+        assert_eq!(alpha_beta_move(&game, SearchLimits::nodes(20_000)), Some(53));
+        play(&mut game, &[2]);
+        let result = AlphaBeta::new(&game).search(&game, SearchLimits::nodes(20_000));
+        assert_eq!(result.best_move, Some(53));
+        assert!(result.is_win());
+    }
+
+    #[test]
+    fn test_overline_is_not_a_win() {
+        // Filling the gap in X X _ X X wins four in a row, unless overlines do not count
+        let mut game = Game::with_size(7, 7, 4);
+        play(&mut game, &[0, 42, 1, 44, 3, 46, 4, 48]);
+        let limits = SearchLimits::nodes(1000).depth(1);
+        let result = AlphaBeta::new(&game).search(&game, limits);
+        assert_eq!(result.best_move, Some(2));
+        assert!(result.score > MATE_BOUND);
+        game.set_restriction(Restriction::Exact);
+        assert!(AlphaBeta::new(&game).search(&game, limits).score < MATE_BOUND);
+    }
+
+    #[test]
+    fn test_renju_avoids_fouls() {
+        // The double three is never chosen for player 1 under renju rules
+        let mut game = Game::with_size(15, 15, 5);
+        game.set_restriction(Restriction::Renju);
+        play(&mut game, &[110, 0, 111, 2, 82, 4, 97, 6]);
+        let best_move = alpha_beta_move(&game, SearchLimits::nodes(2000)).unwrap();
+        assert_ne!(best_move, 112);
+        assert!(game.is_legal(best_move));
+    }
+
+    #[test]
+    fn test_gravity_needs_support() {
+        // Under gravity X wins on top of its column, and a gap in a row is no threat until it can be reached,
+        // so X must block O's threat on the bottom row first
+        let mut game = Game::with_size(7, 6, 4);
+        game.set_gravity(true);
+        play(&mut game, &[35, 36, 28, 37, 21, 40]);
+        assert_eq!(alpha_beta_move(&game, SearchLimits::nodes(10_000)), Some(14));
+
+        let mut game = Game::with_size(4, 4, 3);
+        game.set_gravity(true);
+        play(&mut game, &[15, 12, 8, 14, 10, 11]);
+        let result = AlphaBeta::new(&game).search(&game, SearchLimits::unlimited().depth(1));
+        assert_eq!(result.best_move, Some(13));
+        assert!(result.score < MATE_BOUND);
+    }
+
+    #[test]
+    fn test_node_budget() {
+        // A large board cannot be solved within a small budget, but a move is still returned
+        let mut game = Game::with_size(7, 7, 4);
+        play(&mut game, &[24]);
+        let result = AlphaBeta::new(&game).search(&game, SearchLimits::nodes(5_000));
+        assert!(!result.solved);
+        assert!(result.nodes <= 5_000 + CHECK_INTERVAL);
+        assert!(game.is_legal(result.best_move.unwrap()));
+    }
+
+    #[test]
+    fn test_depth_limit() {
+        // A shallow search stops deepening early without claiming to have solved the game
+        let game = Game::new();
+        let result = AlphaBeta::new(&game).search(&game, SearchLimits::unlimited().depth(2));
+        assert_eq!(result.depth, 2);
+        assert!(!result.solved);
+        assert!(game.is_legal(result.best_move.unwrap()));
+    }
+
+    #[test]
+    fn test_alpha_beta_strategy_never_loses() {
+        // The alpha-beta player never loses the standard game against random moves
+        for _round in 0..5 {
+            let mut game = Game::new();
+            let mut players = [AiPlayer::new(Strategy::Random, Difficulty::Random),
+                               AiPlayer::new(Strategy::AlphaBeta, Difficulty::Perfect)];
+            while !game.end_game {
+                let loc = players[game.curr_player].choose_move(&game).unwrap();
+                game.place(loc).unwrap();
+            }
+            assert_ne!(game.winner(), Some(0));
+        }
+    }
+
+    #[test]
+    fn test_game_over() {
+        // No move is offered once the game has ended
+        let mut game = Game::new();
+        play(&mut game, &[0, 3, 1, 4, 2]);
+        let result = AlphaBeta::new(&game).search(&game, SearchLimits::unlimited());
+        assert_eq!(result.best_move, None);
+        assert!(result.solved);
+    }
+}
@@ -0,0 +1,194 @@
+/*
+*  Exhaustive solver: walks every game that can follow a position to find its theoretical value,
+*  how many distinct games and positions follow it, and how each first move fares. Positions
+*  reached by different move orders are solved once, so the standard 3x3 game takes milliseconds,
+*  but the work still grows with the number of reachable positions, which limits it to small boards.
+*/
+
+use std::collections::HashMap;
+use std::fmt;
+use crate::position::Position;
+use crate::Outcome;
+
+pub const SOLVE_SQUARES: usize = 13;   // most open squares `solve` takes on, which it walks in seconds
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Value {
+    // Result of perfect play for the player to move, ordered from worst to best
+    Loss,
+    Draw,
+    Win,
+}
+
+impl Value {
+    fn flip(self) -> Value {
+        // The same result seen by the opponent
+        match self {
+            Value::Loss => Value::Win,
+            Value::Draw => Value::Draw,
+            Value::Win => Value::Loss,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        // Name the value for players
+        let name = match self {
+            Value::Loss => "loss",
+            Value::Draw => "draw",
+            Value::Win => "win",
+        };
+        write!(formatter, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Tally {
+    // Number of complete games ending each way, for one player
+    pub wins: u64,
+    pub draws: u64,
+    pub losses: u64,
+}
+
+impl Tally {
+    pub fn games(&self) -> u64 {
+        // Returns the number of games counted
+        self.wins + self.draws + self.losses
+    }
+
+    fn flip(self) -> Tally {
+        // The same games counted for the opponent
+        Tally { wins: self.losses, draws: self.draws, losses: self.wins }
+    }
+
+    fn add(self, other: Tally) -> Tally {
+        // Both sets of games together
+        Tally { wins: self.wins + other.wins, draws: self.draws + other.draws, losses: self.losses + other.losses }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MoveSummary {
+    // How one move for the player to move fares
+    pub loc: usize,     // location (index) played
+    pub value: Value,   // result of perfect play after the move, for the player who made it
+    pub games: Tally,   // games following the move, counted for the player who made it
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Solution {
+    // Everything the solver learned about a position
+    pub value: Value,               // result of perfect play for the player to move
+    pub games: Tally,               // every distinct game from the position, counted for the player to move
+    pub positions: usize,           // distinct positions reachable, including the position itself
+    pub moves: Vec<MoveSummary>,    // each legal move, in location (index) order
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    // Solved position: its value and game counts for the player to move
+    value: Value,
+    games: Tally,
+}
+
+pub fn supports(position: &Position) -> bool {
+    // Returns True if the position has at most `SOLVE_SQUARES` open squares; every further open square
+    // multiplies the positions to walk, so e.g. the empty 4x4 board would take minutes and gigabytes
+    (0..position.total_squares()).filter(|&loc| position.piece_at(loc).is_none()).count() <= SOLVE_SQUARES
+}
+
+pub fn solve(position: &Position) -> Solution {
+    // Enumerates the full game tree below `position`, however long that takes, see `supports`
+    let mut solved: HashMap<Position, Node> = HashMap::new();
+    let root = solve_node(position, &mut solved);
+    let moves = position.legal_moves().into_iter()
+        .map(|loc| {
+            let child = solved[&position.apply(loc)];
+            MoveSummary { loc, value: child.value.flip(), games: child.games.flip() }
+        })
+        .collect();
+    Solution { value: root.value, games: root.games, positions: solved.len(), moves }
+}
+
+fn solve_node(position: &Position, solved: &mut HashMap<Position, Node>) -> Node {
+    // Solves one position, reusing the result if another move order already reached it
+    if let Some(&node) = solved.get(position) {
+        return node;
+    }
+    let node = match position.outcome() {
+        Outcome::Win { player, .. } if player == position.to_move() => {
+            Node { value: Value::Win, games: Tally { wins: 1, ..Tally::default() } }
+        },
+        Outcome::Win { .. } => Node { value: Value::Loss, games: Tally { losses: 1, ..Tally::default() } },
+        Outcome::Draw => Node { value: Value::Draw, games: Tally { draws: 1, ..Tally::default() } },
+        Outcome::InProgress => {
+            // the player to move picks the reply that is worst for the opponent
+            let mut value = Value::Loss;
+            let mut games = Tally::default();
+            for loc in position.legal_moves() {
+                let child = solve_node(&position.apply(loc), solved);
+                value = value.max(child.value.flip());
+                games = games.add(child.games.flip());
+            }
+            Node { value, games }
+        },
+    };
+    solved.insert(*position, node);
+    node
+}
+
+
+/***********
+ UNIT TESTS
+************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_standard_game() {
+        // The well-known counts for 3x3: a draw, 255,168 games and 5,478 positions
+        let solution = solve(&Position::new(3, 3, 3));
+        assert_eq!(solution.value, Value::Draw);
+        assert_eq!(solution.games.games(), 255_168);
+        assert_eq!(solution.games, Tally { wins: 131_184, draws: 46_080, losses: 77_904 });
+        assert_eq!(solution.positions, 5_478);
+        assert_eq!(solution.moves.len(), 9);
+        assert!(solution.moves.iter().all(|summary| summary.value == Value::Draw));
+        assert_eq!(solution.moves.iter().map(|summary| summary.games.games()).sum::<u64>(), 255_168);
+    }
+
+    #[test]
+    fn test_per_move_values() {
+        // After X in a corner and O on an edge, X can force a win, but not from every square
+        let position = Position::new(3, 3, 3).apply(0).apply(1);
+        let solution = solve(&position);
+        assert_eq!(solution.value, Value::Win);
+        let value_of = |loc: usize| solution.moves.iter().find(|summary| summary.loc == loc).unwrap().value;
+        assert_eq!(value_of(4), Value::Win);
+        assert_eq!(value_of(2), Value::Draw);
+    }
+
+    #[test]
+    fn test_supports() {
+        // Boards up to `SOLVE_SQUARES` open squares are solved, larger ones only once enough moves are played
+        assert!(supports(&Position::new(3, 3, 3)));
+        assert!(supports(&Position::new(4, 3, 3)));
+        assert!(!supports(&Position::new(4, 4, 4)));
+        assert!(supports(&[0, 5, 10].iter().fold(Position::new(4, 4, 4), |position, &loc| position.apply(loc))));
+        assert!(!supports(&Position::new(15, 15, 5)));
+    }
+
+    #[test]
+    fn test_finished_position() {
+        // A finished game is a single game and a single position
+        let position = [0, 3, 1, 4, 2].iter().fold(Position::new(3, 3, 3), |position, &loc| position.apply(loc));
+        let solution = solve(&position);
+        assert_eq!(solution.value, Value::Loss);
+        assert_eq!(solution.games, Tally { losses: 1, ..Tally::default() });
+        assert_eq!(solution.positions, 1);
+        assert!(solution.moves.is_empty());
+    }
+}
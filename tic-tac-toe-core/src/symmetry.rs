@@ -0,0 +1,139 @@
+/*
+*  Rotations and reflections that map a board onto itself. Positions that are images of one another
+*  under a symmetry play out the same way, so solvers, searches and statistics can share results
+*  between them through the canonical form returned by `Position::canonical`.
+*/
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Symmetry {
+    // A board symmetry, named by where it sends the location in row `x` and column `y`
+    Identity,           // (x, y)
+    FlipRows,           // (height - 1 - x, y)
+    FlipColumns,        // (x, width - 1 - y)
+    HalfTurn,           // (height - 1 - x, width - 1 - y)
+    MainDiagonal,       // (y, x), square boards only
+    AntiDiagonal,       // (width - 1 - y, height - 1 - x), square boards only
+    QuarterTurn,        // (y, height - 1 - x), square boards only
+    ThreeQuarterTurn,   // (width - 1 - y, x), square boards only
+}
+
+impl Symmetry {
+    pub const ALL: [Symmetry; 8] = [Symmetry::Identity, Symmetry::FlipRows, Symmetry::FlipColumns,
+                                    Symmetry::HalfTurn, Symmetry::MainDiagonal, Symmetry::AntiDiagonal,
+                                    Symmetry::QuarterTurn, Symmetry::ThreeQuarterTurn];
+
+    pub fn fits(self, width: usize, height: usize, layers: usize) -> bool {
+        // Returns True if the symmetry maps a board of `layers` stacked `width` x `height` layers onto itself.
+        // Layers are stacked row after row, so flipping the rows also reverses the layers, while turns and
+        // diagonal reflections, which swap rows with columns, only fit a single square layer
+        match self {
+            Symmetry::Identity | Symmetry::FlipRows | Symmetry::FlipColumns | Symmetry::HalfTurn => true,
+            _ => width == height && layers == 1,
+        }
+    }
+
+    pub fn is_upright(self) -> bool {
+        // Returns True if the symmetry keeps every row where it is, so under gravity
+        // pieces still rest on the squares below them
+        matches!(self, Symmetry::Identity | Symmetry::FlipColumns)
+    }
+
+    pub fn inverse(self) -> Symmetry {
+        // Returns the symmetry that undoes this one
+        match self {
+            Symmetry::QuarterTurn => Symmetry::ThreeQuarterTurn,
+            Symmetry::ThreeQuarterTurn => Symmetry::QuarterTurn,
+            symmetry => symmetry,
+        }
+    }
+
+    pub fn map(self, loc: usize, width: usize, height: usize) -> usize {
+        // Returns where the location (index) lands on a `width` x `height` board; on a board of several
+        // layers, `height` counts the rows of every layer
+        let (x, y) = (loc / width, loc % width);
+        let (x, y) = match self {
+            Symmetry::Identity => (x, y),
+            Symmetry::FlipRows => (height - 1 - x, y),
+            Symmetry::FlipColumns => (x, width - 1 - y),
+            Symmetry::HalfTurn => (height - 1 - x, width - 1 - y),
+            Symmetry::MainDiagonal => (y, x),
+            Symmetry::AntiDiagonal => (width - 1 - y, height - 1 - x),
+            Symmetry::QuarterTurn => (y, height - 1 - x),
+            Symmetry::ThreeQuarterTurn => (width - 1 - y, x),
+        };
+        x * width + y
+    }
+}
+
+pub fn symmetries(width: usize, height: usize, layers: usize) -> Vec<Symmetry> {
+    // Returns the symmetries of the board: all eight rotations and reflections of a square board,
+    // or the identity, both mirror images and the half turn of a rectangular or layered one
+    Symmetry::ALL.iter().copied().filter(|symmetry| symmetry.fits(width, height, layers)).collect()
+}
+
+pub fn symmetry_mapping(width: usize, height: usize, layers: usize) -> Vec<Vec<usize>> {
+    // Generates the location permutations that map the board onto itself, in `symmetries` order
+    permutations(&symmetries(width, height, layers), width, height * layers)
+}
+
+pub fn permutations(symmetries: &[Symmetry], width: usize, rows: usize) -> Vec<Vec<usize>> {
+    // Generates the location permutation of each symmetry on a board of `width` columns and `rows` rows
+    symmetries.iter()
+        .map(|symmetry| (0..width * rows).map(|loc| symmetry.map(loc, width, rows)).collect())
+        .collect()
+}
+
+
+/***********
+ UNIT TESTS
+************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_symmetry_mapping() {
+        // Square boards have eight symmetries and rectangular boards four, each a permutation
+        let square = symmetry_mapping(3, 3, 1);
+        assert_eq!(square.len(), 8);
+        assert_eq!(square[0], (0..9).collect::<Vec<usize>>());
+        assert!(square.contains(&vec![6, 3, 0, 7, 4, 1, 8, 5, 2]));    // three-quarter turn
+        for symmetry in &square {
+            let mut sorted = symmetry.clone();
+            sorted.sort();
+            assert_eq!(sorted, (0..9).collect::<Vec<usize>>());
+        }
+
+        let rectangle = symmetry_mapping(4, 2, 1);
+        assert_eq!(rectangle.len(), 4);
+        assert!(rectangle.contains(&vec![7, 6, 5, 4, 3, 2, 1, 0]));    // half turn
+
+        // two 2x2 layers stack into a 2x4 grid, which must not be turned like a square
+        let layered = symmetry_mapping(2, 2, 2);
+        assert_eq!(layered.len(), 4);
+        assert!(layered.contains(&vec![6, 7, 4, 5, 2, 3, 0, 1]));      // rows and layers reversed
+        assert_eq!(symmetries(4, 2, 2), symmetries(4, 4, 4));
+    }
+
+    #[test]
+    fn test_inverse_undoes_symmetry() {
+        // Mapping a location and then mapping it back returns the original location
+        for &(width, height) in &[(3, 3), (4, 4), (5, 3)] {
+            for symmetry in symmetries(width, height, 1) {
+                for loc in 0..width * height {
+                    assert_eq!(symmetry.inverse().map(symmetry.map(loc, width, height), width, height), loc);
+                }
+            }
+        }
+        assert_eq!(Symmetry::QuarterTurn.map(0, 3, 3), 2);
+    }
+
+    #[test]
+    fn test_upright_symmetries() {
+        // Only the identity and the mirror image keep pieces resting on the squares below them
+        let upright: Vec<Symmetry> = Symmetry::ALL.iter().copied().filter(|symmetry| symmetry.is_upright()).collect();
+        assert_eq!(upright, vec![Symmetry::Identity, Symmetry::FlipColumns]);
+        assert_eq!(permutations(&upright, 3, 2), vec![vec![0, 1, 2, 3, 4, 5], vec![2, 1, 0, 5, 4, 3]]);
+    }
+}
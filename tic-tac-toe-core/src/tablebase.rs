@@ -0,0 +1,133 @@
+/*
+*  Solved values and best moves for every position on boards up to 3x3, under standard and misère rules, generated by
+*  `build.rs` when the crate is compiled. Lookups are instant, so perfect play on the standard board
+*  needs no search, even in size-optimized WASM builds.
+*/
+
+use crate::position::Position;
+use crate::solve::Value;
+use crate::{Game, Restriction, Rules};
+
+type Table = (usize, usize, usize, bool, &'static [TableEntry]);    // width, height, win length, misère and entries sorted by key
+type TableEntry = (u32, u8, u16);   // canonical pieces (player 1 in the low bits), value (0 loss, 1 draw, 2 win), best moves
+
+include!(concat!(env!("OUT_DIR"), "/tablebase.rs"));
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    // What the tablebase knows about one position
+    pub value: Value,               // result of perfect play for the player to move
+    pub best_moves: Vec<usize>,     // locations (indices) that reach it, winning soonest or losing latest
+}
+
+fn table(position: &Position) -> Option<&'static [TableEntry]> {
+    // Returns the solved table for the position's board, if it was small enough to solve
+    let misere = position.rules() == Rules::Misere;
+    if position.layers() > 1 || position.restriction() != Restriction::Freestyle || position.gravity() {
+        return None;
+    }
+    TABLES.iter()
+        .find(|&&(width, height, win_length, table_misere, _)| {
+            (width, height, win_length, table_misere) == (position.width(), position.height(), position.win_length(), misere)
+        })
+        .map(|&(_, _, _, _, entries)| entries)
+}
+
+pub fn covers(position: &Position) -> bool {
+    // Returns True if the position's board is in the tablebase
+    table(position).is_some()
+}
+
+pub fn lookup(position: &Position) -> Option<Entry> {
+    // Returns the value and best moves of the position, or None if its board is not in the tablebase
+    // or the position cannot be reached by legal play
+    let entries = table(position)?;
+    let (canonical, symmetry) = position.canonical();
+    let key = (0..canonical.total_squares()).fold(0, |key, loc| match canonical.piece_at(loc) {
+        Some(player) => key | 1 << (loc + 16 * player),
+        None => key,
+    });
+    let index = entries.binary_search_by_key(&key, |&(key, _, _)| key).ok()?;
+    let (_, value, best) = entries[index];
+    let value = match value {
+        0 => Value::Loss,
+        1 => Value::Draw,
+        _ => Value::Win,
+    };
+    let (width, height) = (position.width(), position.height());
+    let mut best_moves: Vec<usize> = (0..position.total_squares())
+        .filter(|&loc| best >> loc & 1 == 1)
+        .map(|loc| symmetry.inverse().map(loc, width, height))
+        .collect();
+    best_moves.sort();
+    Some(Entry { value, best_moves })
+}
+
+pub fn best_move(game: &Game) -> Option<usize> {
+    // Returns one of the best locations (indices) for the current player, picked with the game's
+    // seeded generator, or None if the tablebase does not cover the game
+    let best_moves = lookup(&game.position())?.best_moves;
+    match best_moves.len() {
+        0 => None,
+        count => Some(best_moves[game.rng.gen_range(0, count)]),
+    }
+}
+
+
+/***********
+ UNIT TESTS
+************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solve::solve;
+
+    #[test]
+    fn test_matches_solver() {
+        // The opening positions agree with the exhaustive solver
+        let empty = Position::new(3, 3, 3);
+        let mut positions = vec![empty, empty.apply(0).apply(1), empty.apply(4).apply(0)];
+        positions.extend(empty.legal_moves().into_iter().map(|loc| empty.apply(loc)));
+        for position in positions {
+            let solution = solve(&position);
+            let entry = lookup(&position).unwrap();
+            assert_eq!(entry.value, solution.value);
+            for loc in entry.best_moves {
+                assert_eq!(solution.moves.iter().find(|summary| summary.loc == loc).unwrap().value, solution.value);
+            }
+        }
+    }
+
+    #[test]
+    fn test_wins_soonest() {
+        // With a win on the board, only the winning square is best
+        let position = [0, 3, 1, 4].iter().fold(Position::new(3, 3, 3), |position, &loc| position.apply(loc));
+        assert_eq!(lookup(&position), Some(Entry { value: Value::Win, best_moves: vec![2] }));
+    }
+
+    #[test]
+    fn test_misere() {
+        // Misère 3x3 is a draw, and completing a line is never among the best moves
+        let empty = Position::new(3, 3, 3).with_rules(Rules::Misere);
+        assert_eq!(lookup(&empty).map(|entry| entry.value), Some(Value::Draw));
+        let position = [0, 3, 1, 4].iter().fold(empty, |position, &loc| position.apply(loc));
+        let entry = lookup(&position).unwrap();
+        assert!(!entry.best_moves.contains(&2));
+        assert_eq!(entry.value, solve(&position).value);
+    }
+
+    #[test]
+    fn test_coverage() {
+        // Small boards are covered and larger boards are left to the searches
+        assert!(covers(&Position::new(3, 3, 3)));
+        assert!(covers(&Position::new(3, 2, 2)));
+        assert!(!covers(&Position::new(4, 2, 3)));
+        assert!(!covers(&Position::new(4, 4, 4)));
+        assert!(!covers(&Position::layered(3, 3, 3, 3)));
+        assert!(!covers(&Position::new(3, 3, 2).with_restriction(Restriction::Exact)));
+        assert!(!covers(&Position::new(3, 3, 3).with_gravity(true)));
+        assert_eq!(lookup(&Position::new(4, 4, 4)), None);
+        assert_eq!(lookup(&Position::new(3, 3, 3)).unwrap().best_moves.len(), 9);
+    }
+}
@@ -0,0 +1,35 @@
+/*
+*  Wall-clock timer for search time budgets. `std::time::Instant` is unavailable on
+*  `wasm32-unknown-unknown`, so the browser build reads the javascript clock instead.
+*/
+
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Timer {
+    // Point in time that elapsed time is measured from
+    #[cfg(not(target_arch = "wasm32"))]
+    start: std::time::Instant,      // monotonic start time
+    #[cfg(target_arch = "wasm32")]
+    start: f64,                     // milliseconds since the epoch from `Date.now()`
+}
+
+impl Timer {
+    pub fn start() -> Timer {
+        // Starts timing from now
+        #[cfg(not(target_arch = "wasm32"))]
+        let timer = Timer { start: std::time::Instant::now() };
+        #[cfg(target_arch = "wasm32")]
+        let timer = Timer { start: js_sys::Date::now() };
+        timer
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        // Returns the time passed since the timer started
+        #[cfg(not(target_arch = "wasm32"))]
+        let elapsed = self.start.elapsed();
+        #[cfg(target_arch = "wasm32")]
+        let elapsed = Duration::from_micros(((js_sys::Date::now() - self.start).max(0.0) * 1000.0) as u64);
+        elapsed
+    }
+}
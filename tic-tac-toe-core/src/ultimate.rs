@@ -0,0 +1,332 @@
+/*
+*  Ultimate tic-tac-toe: a 3x3 meta-board of standard 3x3 games. The square played on a small board
+*  sends the opponent to the small board in the same place on the meta-board, winning a small board
+*  claims its square of the meta-board, and three claimed squares in a row win the game. Every board,
+*  including the meta-board, is a `Game`, so the usual win and draw checks decide each of them.
+*/
+
+use std::fmt;
+use crate::rng::SeededRng;
+use crate::{Game, Move, MoveError, Outcome, P1, P2, SIZE};
+
+pub const BOARDS: usize = SIZE * SIZE;          // small boards on the meta-board, and squares on each small board
+pub const SQUARES: usize = BOARDS * BOARDS;     // locations (indices) across every small board
+pub const SIDE: usize = SIZE * SIZE;            // squares along each side of the whole grid
+
+
+#[derive(Debug, PartialEq)]
+pub struct Ultimate {
+    // Nested game state: locations (indices) count the small boards in order, `board * BOARDS + square`
+    pub boards: Vec<Game>,          // small games, in meta-board location (index) order
+    pub meta: Game,                 // meta-board, where winning a small board claims its square for the winner
+    pub curr_player: usize,         // current player
+    pub players: [char; 2],         // players represented by pieces
+    pub next_board: Option<usize>,  // small board the current player was sent to, None to play on any open board
+    pub end_game: bool,             // game status: False if in play, True if ended by win/draw
+    pub outcome: Outcome,           // whether the game is in play, won on the meta-board (and by whom) or drawn
+    pub rng: SeededRng,             // source of every random move, seeded so games can be replayed
+    pub history: Vec<Move>,         // moves played so far, in order
+}
+
+impl Default for Ultimate {
+    fn default() -> Ultimate {
+        Ultimate::new()
+    }
+}
+
+impl Ultimate {
+    pub fn new() -> Self {
+        // Initializes nine empty small boards and the empty meta-board, with player 1 free to play anywhere
+        Self {
+            boards: (0..BOARDS).map(|_| Game::new()).collect(),
+            meta: Game::new(),
+            curr_player: 0,
+            players: [P1, P2],
+            next_board: None,
+            end_game: false,
+            outcome: Outcome::InProgress,
+            rng: SeededRng::from_entropy(),
+            history: vec![],
+        }
+    }
+
+    pub fn set_seed(&mut self, seed: u64) {
+        // Restart the random number generator from `seed` so random moves can be replayed
+        self.rng = SeededRng::new(seed);
+    }
+
+    pub fn seed(&self) -> u64 {
+        // Returns the seed of the random number generator
+        self.rng.seed()
+    }
+
+    pub fn location(&self, board: usize, square: usize) -> Option<usize> {
+        // Returns the location (index) of `square` on small `board`, each counted from 0, or None if off the grid
+        match board < BOARDS && square < BOARDS {
+            true => Some(board * BOARDS + square),
+            false => None,
+        }
+    }
+
+    pub fn grid_location(row: usize, col: usize) -> usize {
+        // Returns the location (index) shown in `row` and `col` of the whole 9x9 grid, as rendered
+        let board = row / SIZE * SIZE + col / SIZE;
+        let square = row % SIZE * SIZE + col % SIZE;
+        board * BOARDS + square
+    }
+
+    pub fn piece_at(&self, loc: usize) -> char {
+        // Returns the piece on the location (index), or a space if it is empty
+        let square = &self.boards[loc / BOARDS].coordinates[loc % BOARDS];
+        self.boards[loc / BOARDS].board[square.x][square.y]
+    }
+
+    pub fn open_boards(&self) -> Vec<usize> {
+        // Returns the small boards the current player may play on: the one they were sent to,
+        // or every small board still in play if that one is finished or this is the first move
+        if self.end_game {
+            return vec![];
+        }
+        match self.next_board {
+            Some(board) => vec![board],
+            None => (0..BOARDS).filter(|&board| !self.boards[board].end_game).collect(),
+        }
+    }
+
+    pub fn is_legal(&self, loc: usize) -> bool {
+        // Returns True if a piece can be placed on the location (index) right now, False otherwise
+        loc < SQUARES && self.open_boards().contains(&(loc / BOARDS)) && self.boards[loc / BOARDS].is_legal(loc % BOARDS)
+    }
+
+    pub fn legal_moves(&self) -> Vec<usize> {
+        // Returns every location (index) where the current player may place a piece
+        self.open_boards().into_iter()
+            .flat_map(|board| self.boards[board].legal_moves().into_iter().map(move |square| board * BOARDS + square))
+            .collect()
+    }
+
+    pub fn random_move(&self) -> Option<usize> {
+        // Return the location (index) for a random, legal move, or None if there is none
+        let moves = self.legal_moves();
+        match moves.len() {
+            0 => None,
+            count => Some(moves[self.rng.gen_range(0, count)]),
+        }
+    }
+
+    pub fn place(&mut self, loc: usize) -> Result<Outcome, MoveError> {
+        // Place the current player's piece on the location (index) and hand the turn over
+        self.make_move(self.curr_player, loc)
+    }
+
+    pub fn make_move(&mut self, player: usize, loc: usize) -> Result<Outcome, MoveError> {
+        // Place `player`'s piece on the location (index) if the move is legal, claiming the small board's
+        // square of the meta-board if the move wins it, and return the outcome of the whole game
+        if self.end_game {
+            return Err(MoveError::GameOver);
+        }
+        if player != self.curr_player {
+            return Err(MoveError::NotYourTurn(player));
+        }
+        if loc >= SQUARES {
+            return Err(MoveError::OutOfRange(loc));
+        }
+        let (board, square) = (loc / BOARDS, loc % BOARDS);
+        if !self.open_boards().contains(&board) {
+            return Err(MoveError::WrongBoard(board));
+        }
+
+        // The small boards do not alternate turns by themselves, so each move is made as the player to move
+        let small = &mut self.boards[board];
+        small.curr_player = player;
+        let small_outcome = small.place(square).map_err(|_| MoveError::Occupied(loc))?;
+        if let Outcome::Win { player: winner, .. } = small_outcome {
+            self.meta.curr_player = winner;
+            self.meta.place(board).expect("a small board is won only once");
+        }
+        self.history.push(Move { player, loc, symbol: player });
+
+        // The meta-board decides the game; with no line there, it is drawn once every small board is finished
+        self.outcome = match self.meta.outcome {
+            Outcome::Win { .. } => self.meta.outcome.clone(),
+            _ if self.boards.iter().all(|small| small.end_game) => Outcome::Draw,
+            _ => Outcome::InProgress,
+        };
+        self.end_game = self.outcome != Outcome::InProgress;
+        self.next_board = match self.boards[square].end_game {
+            true => None,
+            false => Some(square),
+        };
+        self.curr_player = 1 - player;
+        Ok(self.outcome.clone())
+    }
+
+    pub fn winner(&self) -> Option<usize> {
+        // Returns the player who won the game, if any
+        match self.outcome {
+            Outcome::Win { player, .. } => Some(player),
+            _ => None,
+        }
+    }
+
+    pub fn board_winner(&self, board: usize) -> Option<usize> {
+        // Returns the player who claimed the small board, if any
+        self.boards[board].winner()
+    }
+
+    pub fn reset(&mut self) {
+        // Reset Game
+        for small in &mut self.boards {
+            small.reset();
+        }
+        self.meta.reset();
+        self.curr_player = 0;
+        self.next_board = None;
+        self.end_game = false;
+        self.outcome = Outcome::InProgress;
+        self.history.clear();
+    }
+}
+
+impl fmt::Display for Ultimate {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        // Display the whole grid, with double lines between small boards and every square of a
+        // claimed small board showing its winner's piece
+        let game_status = match (self.end_game, self.next_board) {
+            (true, _) => "ended".to_string(),
+            (false, Some(board)) => format!("in play, {}'s turn on board {}", self.players[self.curr_player], board + 1),
+            (false, None) => format!("in play, {}'s turn on any board", self.players[self.curr_player]),
+        };
+        writeln!(formatter, "\nGame {}:", game_status)?;
+        let small_divider = vec!["-".repeat(4 * SIZE - 1); SIZE].join("-||-");
+        let meta_divider = "=".repeat(SIZE * (4 * SIZE - 1) + 4 * (SIZE - 1));
+        for row in 0..SIDE {
+            let boards: Vec<String> = (0..SIZE)
+                .map(|board_col| {
+                    let squares: Vec<String> = (0..SIZE)
+                        .map(|col| Ultimate::grid_location(row, board_col * SIZE + col))
+                        .map(|loc| match self.board_winner(loc / BOARDS) {
+                            Some(player) => self.players[player].to_string(),
+                            None => self.piece_at(loc).to_string(),
+                        })
+                        .collect();
+                    squares.join(" | ")
+                })
+                .collect();
+            writeln!(formatter, "  {}", boards.join("  ||  "))?;
+            if row + 1 == SIDE {
+                break;
+            }
+            match (row + 1) % SIZE {
+                0 => writeln!(formatter, " {}", meta_divider)?,
+                _ => writeln!(formatter, " {}", small_divider)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+
+/***********
+ UNIT TESTS
+************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn play(game: &mut Ultimate, moves: &[(usize, usize)]) {
+        // Plays each (small board, square) pair in order
+        for &(board, square) in moves {
+            game.place(board * BOARDS + square).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_square_sends_opponent() {
+        // The square played picks the small board the opponent must play on next
+        let mut game = Ultimate::new();
+        assert_eq!(game.legal_moves().len(), SQUARES);
+        play(&mut game, &[(4, 2)]);
+        assert_eq!(game.next_board, Some(2));
+        assert_eq!(game.legal_moves(), (18..27).collect::<Vec<usize>>());
+        assert_eq!(game.place(40), Err(MoveError::WrongBoard(4)));
+        assert_eq!(game.piece_at(38), P1);
+        assert_eq!(Ultimate::grid_location(0, 8), 20);
+        assert_eq!(Ultimate::grid_location(4, 4), 40);
+    }
+
+    #[test]
+    fn test_small_win_claims_meta_square() {
+        // Winning a small board claims its square, and a finished board frees the next player
+        let mut game = Ultimate::new();
+        play(&mut game, &[(0, 0), (0, 3), (3, 0), (0, 4), (4, 0), (0, 5)]);
+        assert_eq!(game.board_winner(0), Some(1));
+        assert_eq!(game.meta.cells()[0], P2);
+        assert_eq!(game.next_board, Some(5));
+        play(&mut game, &[(5, 0)]);
+        assert_eq!(game.next_board, None);
+        assert!(!game.legal_moves().iter().any(|&loc| loc < BOARDS));
+        assert_eq!(game.place(8), Err(MoveError::WrongBoard(0)));
+    }
+
+    #[test]
+    fn test_meta_line_wins() {
+        // A won game reports a meta-board line of small boards all claimed by the winner
+        let mut wins = 0;
+        for seed in 0..20 {
+            let mut game = Ultimate::new();
+            game.set_seed(seed);
+            while !game.end_game {
+                let loc = game.random_move().unwrap();
+                game.place(loc).unwrap();
+            }
+            if let Outcome::Win { player, ref line } = game.outcome {
+                assert!(line.iter().all(|&board| game.board_winner(board) == Some(player)));
+                assert_eq!(game.outcome, game.meta.outcome);
+                assert!(game.legal_moves().is_empty());
+                assert_eq!(game.random_move(), None);
+                assert_eq!(game.place(80), Err(MoveError::GameOver));
+                wins += 1;
+            }
+        }
+        assert!(wins > 0);
+    }
+
+    #[test]
+    fn test_random_game_ends() {
+        // Random play always reaches a win or a draw, and a seed replays the same game
+        let play_out = |seed: u64| -> Vec<Move> {
+            let mut game = Ultimate::new();
+            game.set_seed(seed);
+            while !game.end_game {
+                let loc = game.random_move().unwrap();
+                assert!(game.is_legal(loc));
+                game.place(loc).unwrap();
+            }
+            assert_ne!(game.outcome, Outcome::InProgress);
+            game.history
+        };
+        assert_eq!(play_out(11), play_out(11));
+
+        let mut game = Ultimate::new();
+        play(&mut game, &[(4, 4), (4, 0)]);
+        game.reset();
+        assert_eq!(game.legal_moves().len(), SQUARES);
+        assert_eq!(game.piece_at(40), ' ');
+    }
+
+    #[test]
+    fn test_display() {
+        // The grid shows small boards side by side, and claimed boards in their winner's piece
+        let mut game = Ultimate::new();
+        play(&mut game, &[(0, 0), (0, 3), (3, 0), (0, 4), (4, 0), (0, 5), (5, 8)]);
+        let shown = format!("{}", game);
+        let lines: Vec<&str> = shown.lines().collect();
+        assert_eq!(lines[1], "Game in play, O's turn on board 9:");
+        assert_eq!(lines[2], "  O | O | O  ||    |   |    ||    |   |  ");
+        assert_eq!(lines[7], format!(" {}", "=".repeat(41)));
+        assert_eq!(lines[8], "  X |   |    ||  X |   |    ||    |   |  ");
+        assert_eq!(lines[12], "    |   |    ||    |   |    ||    |   | X");
+        assert_eq!(lines.len(), 2 + 17);
+    }
+}
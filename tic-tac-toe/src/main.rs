@@ -1,336 +1,845 @@
-use std::fmt;
-use rand::{thread_rng, Rng};
+use std::env;
 use std::io::{stdin, stdout, Write};
+use std::time::Duration;
+use tic_tac_toe_core::player::{AiPlayer, Human, Player};
+use tic_tac_toe_core::bitboard::MAX_SQUARES;
+use tic_tac_toe_core::hint::{evaluate, Evaluation};
+use tic_tac_toe_core::mcts::MctsConfig;
+use tic_tac_toe_core::notakto::{self, Notakto};
+use tic_tac_toe_core::position::Position;
+use tic_tac_toe_core::solve::{self, solve, Solution, Value, SOLVE_SQUARES};
+use tic_tac_toe_core::ultimate::{Ultimate, SIDE, SQUARES};
+use tic_tac_toe_core::{board_name, index_width, Difficulty, Game, MoveError, Outcome, Restriction, Rules, Strategy, P1, P2, SIZE};
 
 const P1_PLAY: bool = false;    // player 1 is set to `false` for automated play to start
 const P2_PLAY: bool = true;     // player 2 is set to `true` for manual play to start
-const P1: char = 'X';           // player 1's piece
-const P2: char = 'O';           // player 2's piece
-const SIZE: usize = 3;          // row/col sizes for board
-const NO_WIN: usize = 9;        // default, invalid value to represent no winner
-const BOARD_IDX: [[char; 3]; 3] = [['0', '1', '2'],     // indexed board for user's reference
-                                   ['3', '4', '5'], 
-                                   ['6', '7', '8']];
+const USAGE: &str = "usage: tic-tac-toe [--width N] [--height N] [--layers N] [--win N] [--ai random|minimax|alphabeta|mcts]\n                   [--level random|easy|medium|hard|perfect] [--seed N] [--misere] [--exact|--renju] [--gravity] [--wild]\n                   [--playouts N] [--mcts-time MS] [--exploration F]\n       tic-tac-toe --connect-four [--width N] [--height N] [--win N] [--ai ...] [--level ...] [--seed N]\n       tic-tac-toe --gomoku [--exact|--renju] [--ai ...] [--level ...] [--seed N]\n       tic-tac-toe --ultimate [--seed N]\n       tic-tac-toe --notakto [--boards N] [--level ...] [--seed N]\n       tic-tac-toe solve [--width N] [--height N] [--layers N] [--win N] [--misere] [--exact|--renju] [--gravity] [--moves LOC,LOC,...]";
+const CONNECT_FOUR: (usize, usize, usize) = (7, 6, 4);    // width, height and win length of the `--connect-four` board
+const GOMOKU_SIZE: usize = 15;  // rows and columns of the `--gomoku` board
+const GOMOKU_WIN: usize = 5;    // pieces in a row needed to win with `--gomoku`
+const NOTATION_SQUARES: usize = 100;    // flat boards with more squares are shown and entered by column letter and row number
 
 
 #[derive(Debug, PartialEq)]
-struct AutoPlay {
-    // Struct detailing whether players move automatically or manually
-    play_type: [bool; 2],           // True: automatic random moves, False: manual moves
-    play_type_str: [String; 2],     // "automatic" or "manual"
+enum Command {
+    // What a manual player typed
+    Place(usize),   // place a piece on the location (index)
+    PlacePiece(usize, usize),   // in wild games, place the piece (index into `players`) on the location (index)
+    Undo,           // take back the player's last move
+    Redo,           // replay the move taken back by the last undo
+    Hint,           // show how every open square fares with perfect play
 }
 
-impl Default for AutoPlay {
-    fn default() -> AutoPlay {
-        // By default, both players are automated to play random, legal moves
-        AutoPlay{ play_type: [true, true], 
-                  play_type_str: ["automatic".to_string(), "automatic".to_string()]}
-    }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Variant {
+    // Which game is played
+    Standard,   // one board of any size, including layered boards
+    Ultimate,   // ultimate tic-tac-toe: a 3x3 meta-board of 3x3 boards
+    Notakto,    // notakto: X only on one or more 3x3 boards, where killing the last board loses
 }
 
 #[derive(Debug, PartialEq)]
-struct Coord {
-    // Struct for mapping array indices to coordinates
-    x: usize,       // x-coordinate
-    y: usize,       // y-coordinate
-    legal: bool,    // flag: True if it is legal to place a piece on the coordinate, False if coordinate is already full
+struct Options {
+    // Command-line settings for the board
+    width: usize,       // number of columns on the board
+    height: usize,      // number of rows in each layer of the board
+    layers: usize,      // number of layers stacked into the board, 1 for a flat board
+    win_length: usize,  // number of pieces in a row needed to win
+    strategy: Strategy, // how automatic players choose their moves
+    difficulty: Difficulty, // how strongly automatic players play
+    playouts: Option<u64>,  // playouts per move for `--ai mcts` in place of the level's, None to follow the level
+    mcts_time: Option<Duration>,    // time limit per move for `--ai mcts`, None for no limit
+    exploration: Option<f64>,   // UCT exploration constant for `--ai mcts`, None for the default
+    seed: Option<u64>,  // seed for random moves, None for a fresh one
+    rules: Rules,       // whether completing a line wins or loses
+    restriction: Restriction,   // whether overlines count and which moves are fouls for player 1
+    gravity: bool,      // True if pieces drop to the lowest open square of the chosen column
+    wild: bool,         // True if each player may place either piece
+    variant: Variant,   // which game is played
+    boards: usize,      // number of boards in a game of notakto
 }
 
-#[derive(Debug, PartialEq)]
-struct WinState {
-    // Struct containing representations of win states
-    p1_win_state: Vec<char>,    // represents player 1's win state
-    p2_win_state: Vec<char>,    // represents player 2's win state
-}
-
-impl Default for WinState {
-    fn default() -> WinState {
-        // Generates winning state sized to default `SIZE`
-        let mut win_state = WinState { p1_win_state: vec![], p2_win_state: vec![] };
-        for _i in 0..SIZE {
-            win_state.p1_win_state.push(P1);
-            win_state.p2_win_state.push(P2);
+impl Default for Options {
+    fn default() -> Options {
+        // By default, play the standard 3x3 game against random moves
+        Options { width: SIZE, height: SIZE, layers: 1, win_length: SIZE,
+                  strategy: Strategy::AlphaBeta, difficulty: Difficulty::Random,
+                  playouts: None, mcts_time: None, exploration: None, seed: None, rules: Rules::Standard,
+                  restriction: Restriction::Freestyle, gravity: false, wild: false,
+                  variant: Variant::Standard, boards: 1 }
+    }
+}
+
+fn parse_options(args: &[String]) -> Result<Options, String> {
+    // Reads `--width`, `--height`, `--layers`, `--win`, `--boards`, `--playouts` and `--mcts-time` flags,
+    // each followed by a positive number, the `--ai` flag followed by a strategy name, the `--level` flag followed
+    // by a difficulty, the `--seed` flag followed by any number, the `--exploration` flag followed by a number
+    // that is not negative and the `--misere`, `--exact`, `--renju`, `--gravity`, `--wild`,
+    // `--connect-four`, `--gomoku`, `--ultimate` and `--notakto` flags on their own. `--connect-four` turns on gravity
+    // on the 7x6 board won by four in a row and `--gomoku` sets up the 15x15 board won by five in a row;
+    // later board flags can still change either size
+    let mut options = Options::default();
+    let mut args = args.iter();
+    while let Some(flag) = args.next() {
+        if flag == "--misere" {
+            options.rules = Rules::Misere;
+            continue;
+        }
+        if flag == "--exact" || flag == "--renju" {
+            options.restriction = if flag == "--exact" { Restriction::Exact } else { Restriction::Renju };
+            continue;
+        }
+        if flag == "--gravity" {
+            options.gravity = true;
+            continue;
+        }
+        if flag == "--wild" {
+            options.wild = true;
+            continue;
+        }
+        if flag == "--connect-four" {
+            (options.width, options.height, options.win_length) = CONNECT_FOUR;
+            options.gravity = true;
+            continue;
+        }
+        if flag == "--gomoku" {
+            options.width = GOMOKU_SIZE;
+            options.height = GOMOKU_SIZE;
+            options.win_length = GOMOKU_WIN;
+            continue;
+        }
+        if flag == "--ultimate" {
+            options.variant = Variant::Ultimate;
+            continue;
+        }
+        if flag == "--notakto" {
+            options.variant = Variant::Notakto;
+            continue;
+        }
+        if flag == "--ai" {
+            options.strategy = match args.next().map(String::as_str) {
+                Some("random") => Strategy::Random,
+                Some("minimax") => Strategy::Minimax,
+                Some("alphabeta") => Strategy::AlphaBeta,
+                Some("mcts") => Strategy::Mcts,
+                _ => return Err(format!("{} expects `random`, `minimax`, `alphabeta` or `mcts`", flag)),
+            };
+            continue;
+        }
+        if flag == "--level" {
+            options.difficulty = match args.next().map(String::as_str) {
+                Some("random") => Difficulty::Random,
+                Some("easy") => Difficulty::Easy,
+                Some("medium") => Difficulty::Medium,
+                Some("hard") => Difficulty::Hard,
+                Some("perfect") => Difficulty::Perfect,
+                _ => return Err(format!("{} expects `random`, `easy`, `medium`, `hard` or `perfect`", flag)),
+            };
+            continue;
+        }
+        if flag == "--seed" {
+            options.seed = match args.next().map(|value| value.parse::<u64>()) {
+                Some(Ok(seed)) => Some(seed),
+                _ => return Err(format!("{} expects a number", flag)),
+            };
+            continue;
+        }
+        if flag == "--exploration" {
+            options.exploration = match args.next().map(|value| value.parse::<f64>()) {
+                Some(Ok(exploration)) if exploration.is_finite() && exploration >= 0.0 => Some(exploration),
+                _ => return Err(format!("{} expects a number that is not negative", flag)),
+            };
+            continue;
         }
-        win_state
+        let value = match args.next().map(|value| value.parse::<usize>()) {
+            Some(Ok(value)) if value > 0 => value,
+            _ => return Err(format!("{} expects a positive number", flag)),
+        };
+        match flag.as_str() {
+            "--width" => options.width = value,
+            "--height" => options.height = value,
+            "--layers" => options.layers = value,
+            "--win" => options.win_length = value,
+            "--boards" => options.boards = value,
+            "--playouts" => options.playouts = Some(value as u64),
+            "--mcts-time" => options.mcts_time = Some(Duration::from_millis(value as u64)),
+            _ => return Err(format!("unknown option {}", flag)),
+        }
+    }
+    let board = board_name(options.width, options.height, options.layers);
+    if options.width * options.height * options.layers > MAX_SQUARES {
+        return Err(format!("a {} board has more than {} squares", board, MAX_SQUARES));
+    }
+    if options.win_length > options.width.max(options.height).max(options.layers) {
+        return Err(format!("a win length of {} does not fit on a {} board", options.win_length, board));
+    }
+    if options.gravity && options.layers > 1 {
+        return Err(format!("gravity needs a flat board, not a {} board", board));
+    }
+    let tuned = options.playouts.is_some() || options.mcts_time.is_some() || options.exploration.is_some();
+    if tuned && options.strategy != Strategy::Mcts {
+        return Err("--playouts, --mcts-time and --exploration tune `--ai mcts` only".to_string());
     }
+    let ai = ai_player(&options);
+    if options.variant == Variant::Standard && options.wild && !ai.supports(&new_game(&options)) {
+        return Err(format!("automatic players can only move at random in wild games, not play {}", ai.name()));
+    }
+    if options.variant == Variant::Standard && !ai.supports(&new_game(&options)) {
+        return Err(format!("{} play cannot search a {} board, so pick a lower --level or a smaller board", ai.name(), board));
+    }
+    Ok(options)
 }
 
-#[derive(Debug, PartialEq)]
-struct Game {
-    // Struct with tic-tac-toe game settings and components
-    board: [[char; SIZE]; SIZE],    // tic tac toe board
-    curr_player: usize,             // current player 
-    players: [char; 2],             // players represented by pieces
-    auto_play: AutoPlay,            // type of play for each player
-    end_game: bool,                 // game status: False if in play, True if ended by win/draw
-    coordinates: Vec<Coord>,        // coordinates for moves
-    win_states: WinState,           // win states for players
-    winner: usize,                  // specifies winner if there is one
-}
-
-impl Game {
-    fn new() -> Self {
-        // Initializes the game board, first player piece and default autoplay for both players
-        Self {
-            board: [[' ', ' ', ' '], 
-                    [' ', ' ', ' '], 
-                    [' ', ' ', ' ']],
-            curr_player: 0,
-            players: [P1, P2],
-            auto_play: AutoPlay::default(), 
-            end_game: false,
-            coordinates: coord_mapping(),
-            win_states: WinState::default(),
-            winner: NO_WIN,
+fn ai_player(options: &Options) -> AiPlayer {
+    // Builds the automatic player for the chosen strategy and level, with any MCTS flags tuning its search
+    match options.strategy {
+        Strategy::Mcts => {
+            let config = MctsConfig::for_difficulty(options.difficulty)
+                .tuned(options.playouts, options.mcts_time, options.exploration);
+            AiPlayer::with_mcts(options.difficulty, config)
+        },
+        strategy => AiPlayer::new(strategy, options.difficulty),
+    }
+}
+
+fn new_game(options: &Options) -> Game {
+    // Builds the empty board with the chosen size, rules and seed
+    let mut game = Game::with_layers(options.width, options.height, options.layers, options.win_length);
+    game.set_rules(options.rules);
+    game.set_restriction(options.restriction);
+    game.set_gravity(options.gravity);
+    game.set_wild(options.wild);
+    if let Some(seed) = options.seed {
+        game.set_seed(seed);
+    }
+    game
+}
+
+fn parse_solve_args(args: &[String]) -> Result<(Options, Vec<usize>), String> {
+    // Reads the board flags for the `solve` subcommand, plus `--moves` followed by
+    // comma-separated locations (indices) played from the empty board
+    let mut moves = vec![];
+    let mut board_args = vec![];
+    let mut args = args.iter();
+    while let Some(flag) = args.next() {
+        if flag != "--moves" {
+            board_args.push(flag.clone());
+            continue;
         }
+        moves = match args.next().map(|value| value.split(',').map(str::parse::<usize>).collect()) {
+            Some(Ok(moves)) => moves,
+            _ => return Err(format!("{} expects locations separated by commas, e.g. `0,4`", flag)),
+        };
+    }
+    parse_options(&board_args).map(|options| (options, moves))
+}
+
+fn solve_position(options: &Options, moves: &[usize]) -> Result<Position, String> {
+    // Plays the moves from the empty board, reporting the first one that is not allowed,
+    // that the game cannot be solved or that too many open squares are left to solve it
+    if options.wild {
+        return Err("wild games cannot be solved".to_string());
     }
+    let empty = Position::layered(options.width, options.height, options.layers, options.win_length)
+        .with_rules(options.rules)
+        .with_restriction(options.restriction)
+        .with_gravity(options.gravity);
+    let position = moves.iter().try_fold(empty, |position, &loc| {
+        position.try_apply(loc).map_err(|error| format!("cannot play {}: {}", loc, error))
+    })?;
+    if !solve::supports(&position) {
+        return Err(format!("a {} board has too many open squares to solve, so play more --moves to leave at most {}",
+                           board_name(options.width, options.height, options.layers), SOLVE_SQUARES));
+    }
+    Ok(position)
+}
 
-    fn start(&mut self, p1_auto: bool, p2_auto: bool) {
-        // Set the automatic/manual play settings for each player in order to start the game
-        // Create the coordinates per game size
-        self.auto_play.play_type = [p1_auto, p2_auto];
+fn solve_report(position: &Position, solution: &Solution) -> String {
+    // Describes the solved position: its value, game and position counts, and how each move fares,
+    // with every count given for the player to move
+    let (mover, opponent) = match position.to_move() {
+        0 => (P1, P2),
+        _ => (P2, P1),
+    };
+    let games = solution.games;
+    let mut report = format!("{} to move :: {}\n", mover, solution.value);
+    report += &format!("Games     :: {} ({} wins {}, draws {}, {} wins {})\n",
+                       games.games(), mover, games.wins, games.draws, opponent, games.losses);
+    report += &format!("Positions :: {}\n", solution.positions);
+    if solution.moves.is_empty() {
+        return report;
+    }
+    report += &format!("\n{:>5} | {:>5} | {:>8} | {:>8} | {:>8}\n", "move", "value", "wins", "draws", "losses");
+    for summary in &solution.moves {
+        report += &format!("{:>5} | {:>5} | {:>8} | {:>8} | {:>8}\n", summary.loc, summary.value.to_string(),
+                           summary.games.wins, summary.games.draws, summary.games.losses);
+    }
+    report
+}
 
-        match self.auto_play {
-            AutoPlay { play_type: [false, false], .. } => {
-                self.auto_play.play_type_str = ["manual".to_string(), "manual".to_string()];
+fn run_solve(args: &[String]) {
+    // Solve the position given on the command line and print what was learned
+    let position = parse_solve_args(args).and_then(|(options, moves)| solve_position(&options, &moves));
+    match position {
+        Ok(position) => print!("{}", solve_report(&position, &solve(&position))),
+        Err(error) => {
+            eprintln!("{}\n{}", error, USAGE);
+            std::process::exit(2);
+        },
+    }
+}
+
+fn start(options: &Options, p1_auto: bool, p2_auto: bool) -> [Box<dyn Player>; 2] {
+    // Set up an automatic or manual player for each side and announce them
+    let player = |auto: bool| -> Box<dyn Player> {
+        match auto {
+            true => Box::new(ai_player(options)),
+            false => Box::new(Human),
+        }
+    };
+    let players = [player(p1_auto), player(p2_auto)];
+    println!("\nPlayer 1 :: {} ({} play)\nPlayer 2 :: {} ({} play)\n", P1, players[0].name(),
+                                                                       P2, players[1].name());
+    players
+}
+
+fn update(game: &mut Game, players: &mut [Box<dyn Player>; 2]) {
+    // Have the current player choose a location for their move, asking the user if they play manually;
+    // in wild games automatic players also pick either piece at random
+    match players[game.curr_player].choose_move(game) {
+        Some(loc) => {
+            game.place_symbol(loc, game.random_piece(loc)).expect("automatic moves are always legal");
+        },
+        None => manual_move(game),
+    }
+}
+
+fn manual_move(game: &mut Game) {
+    // Manual Move: Ask the user for the location where they want to place their piece,
+    // or whether to undo or redo a move or see a hint
+    if game.gravity {
+        println!("\nWhich column do you want to drop your piece into? (a number from 1 to {}, or `u` to undo, \
+                  `r` to redo, `h` for a hint)", game.width);
+        print!("{}\n\n", column_board(game));
+    } else if uses_notation(game) {
+        println!("\nWhere do you want to place your piece? (a square such as {}, or `u` to undo, `r` to redo, \
+                  `h` for a hint)", square_name(game, game.total_squares() / 2));
+    } else if game.wild {
+        let coordinates = if game.layers > 1 { "layer,row,column" } else { "row,column" };
+        println!("\nWhere do you want to place a piece, and which? (a number or {} counting from 1, then {} or {}, \
+                  e.g. `4 {}`, or `u` to undo, `r` to redo)", coordinates, P1, P2, P2);
+        display_indexed_board(game);
+    } else {
+        let coordinates = if game.layers > 1 { "layer,row,column" } else { "row,column" };
+        println!("\nWhere do you want to place your piece? (a number or {} counting from 1, or `u` to undo, \
+                  `r` to redo, `h` for a hint)", coordinates);
+        display_indexed_board(game);
+    }
+
+    // Get user's choice for piece placement until the engine accepts it
+    loop {
+        let (loc, symbol) = match get_user_input(game) {
+            Command::Place(loc) => (loc, game.curr_player),
+            Command::PlacePiece(loc, symbol) => (loc, symbol),
+            Command::Undo => return rewind(game, Game::undo_turn, "undo"),
+            Command::Redo => return rewind(game, Game::redo_turn, "redo"),
+            Command::Hint => {
+                match evaluate(game) {
+                    Some(evaluations) => {
+                        print!("\n{}", hint_board(game, &evaluations));
+                        println!("\nW/L: win/loss in that many moves, D: draw, ?: unknown\nWhere do you want to place your piece? ");
+                    },
+                    None => println!("\nHints are not available in wild games. Where do you want to place a piece? "),
+                }
+                continue;
             },
-            AutoPlay { play_type: [true, false], .. } => {
-                self.auto_play.play_type_str = ["automatic".to_string(), "manual".to_string()];
+        };
+        match game.place_symbol(loc, symbol) {
+            Ok(_) => return,
+            Err(MoveError::Occupied(_)) => {
+                println!("\nA piece is already placed there. Please enter a valid location: ");
             },
-            AutoPlay { play_type: [false, true], .. } => {
-                self.auto_play.play_type_str = ["manual".to_string(), "automatic".to_string()];
+            Err(MoveError::ColumnFull(_)) => {
+                println!("\nThat column is full. Please enter a valid column: ");
             },
-            AutoPlay { .. } => {
-                self.auto_play.play_type_str = ["automatic".to_string(), "automatic".to_string()];
+            Err(MoveError::Forbidden(_)) => {
+                println!("\nThat move is a foul under renju rules. Please enter a valid location: ");
+            },
+            Err(error) => {
+                println!("\n{}. Please enter a valid location: ", error);
             },
         }
-        println!("\nPlayer 1 :: {} ({} play)\nPlayer 2 :: {} ({} play)\n", P1, &self.auto_play.play_type_str[0], 
-                                                                           P2, &self.auto_play.play_type_str[1]);
     }
+}
 
-    fn update(&mut self) { 
-        // Have the current player choose a location for their move 
-        let loc: usize = match &self.auto_play.play_type[self.curr_player] {
-            true => self.auto_move(),
-            false => self.manual_move(),
-        };
+fn rewind(game: &mut Game, step: fn(&mut Game) -> usize, action: &str) {
+    // Undo or redo moves until it is the manual player's turn again and report how many changed
+    match step(game) {
+        0 => println!("\nThere is no move to {}.", action),
+        steps => println!("\n{}: {} move(s)", action, steps),
+    }
+}
 
-        // Update the board and coordinates
-        let x = self.coordinates[loc].x;
-        let y = self.coordinates[loc].y;
-        self.board[x][y] = self.players[self.curr_player];
-        self.coordinates[loc].legal = false;
+fn display_indexed_board(game: &Game) {
+    // Displays board with index repreesentations for placing pieces
+    print!("{}", indexed_board(game));
+    println!("\n");
+}
 
-        // Check for endgame and change players
-        self.end_game = self.is_endgame();
-        self.curr_player = self.switch_player();
-    }
+fn indexed_board(game: &Game) -> String {
+    // Builds the board with each square labelled by its location (index)
+    let labels: Vec<String> = (0..game.total_squares()).map(|loc| loc.to_string()).collect();
+    labelled_board(game, &labels)
+}
 
-    fn switch_player(&mut self) -> usize {
-        // Switch current player
-        if self.curr_player == 0 { 1 } else { 0 }
-    }
+fn hint_board(game: &Game, evaluations: &[Evaluation]) -> String {
+    // Builds the board with each open square labelled by how it fares for the player to move
+    // and each occupied square showing its piece
+    let labels: Vec<String> = game.cells().iter().enumerate()
+        .map(|(loc, piece)| match evaluations.iter().find(|evaluation| evaluation.loc == loc) {
+            Some(evaluation) => match (evaluation.value, evaluation.plies) {
+                (Some(Value::Win), Some(plies)) => format!("W{}", plies),
+                (Some(Value::Loss), Some(plies)) => format!("L{}", plies),
+                (Some(Value::Draw), _) => "D".to_string(),
+                _ => "?".to_string(),
+            },
+            None => piece.to_string(),
+        })
+        .collect();
+    labelled_board(game, &labels)
+}
 
-    fn auto_move(&mut self) -> usize {
-        // Automated Move: Return the location (index) for a random, legal move
-        let max_rng = SIZE * SIZE;
-        let mut rng = thread_rng();
-        let mut loc = rng.gen_range(0, max_rng);
-        let mut valid: bool = self.coordinates[loc].legal;
+fn column_board(game: &Game) -> String {
+    // Builds the board showing each piece, with the column numbers, counted from 1, along the bottom
+    let cell_width = game.width.to_string().len();
+    let labels: Vec<String> = game.cells().iter().map(|piece| format!("{:>width$}", piece, width = cell_width)).collect();
+    let numbers: Vec<String> = (1..=game.width).map(|column| format!("{:>width$}", column, width = cell_width)).collect();
+    labelled_board(game, &labels) + &format!("  {}\n", numbers.join("   "))
+}
 
-        // Make sure the move is valid
-        while valid == false {
-            loc = rng.gen_range(0, max_rng);
-            valid = self.coordinates[loc].legal;
+fn labelled_board(game: &Game, labels: &[String]) -> String {
+    // Builds the board with a label in each square, in location (index) order, padded to the widest label;
+    // layered boards show each layer under its own heading
+    let cell_width = labels.iter().map(String::len).max().unwrap_or(1);
+    let divider = "-".repeat((cell_width + 3) * game.width - 1);
+    let mut board_state = String::new();
+    for (layer, layer_labels) in labels.chunks(game.width * game.height).enumerate() {
+        if game.layers > 1 {
+            board_state += &format!("{}  Layer {}\n", if layer > 0 { "\n" } else { "" }, layer + 1);
+        }
+        for (row, squares) in layer_labels.chunks(game.width).enumerate() {
+            let squares: Vec<String> = squares.iter().map(|label| format!("{:>width$}", label, width = cell_width)).collect();
+            board_state += &format!("  {}\n", squares.join(" | "));
+            if row + 1 < game.height {
+                board_state += &format!(" {}\n", divider);
+            }
         }
-        loc
     }
+    board_state
+}
 
-    fn manual_move(&mut self) -> usize {
-        // Manual Move: Ask the user for the location where they want to place their piece
-        println!("\nWhere do you want to place your piece? ");
-        self.display_indexed_board();
+fn uses_notation(game: &Game) -> bool {
+    // Returns True if the board is too large for location (index) labels,
+    // so squares are named by column letter and row number instead
+    game.has_notation() && game.total_squares() > NOTATION_SQUARES
+}
 
-        // Get user's choice for piece placement
-        let mut loc = self.get_user_input();
-        let mut valid: bool = self.coordinates[loc].legal;
+fn square_name(game: &Game, loc: usize) -> String {
+    // Names the location (index) the way the user enters it
+    match uses_notation(game) {
+        true => game.notation(loc).unwrap_or_else(|| loc.to_string()),
+        false => loc.to_string(),
+    }
+}
 
-        // Make sure the move is valid
-        while valid == false {
-            println!("\nA piece is already placed there. Please enter a valid location: ");
-            loc = self.get_user_input();
-            valid = self.coordinates[loc].legal;
-        }
-        loc
+fn coordinate_board(game: &Game) -> String {
+    // Builds the board with column letters above and below and row numbers, counted from the bottom,
+    // either side, showing each piece and a `.` on each open square
+    let row_width = game.height.to_string().len();
+    let letters: Vec<String> = (0..game.width).map(|col| ((b'a' + col as u8) as char).to_string()).collect();
+    let header = format!("  {:>width$} {}\n", "", letters.join(" "), width = row_width);
+    let mut board_state = header.clone();
+    for (row, squares) in game.board.iter().enumerate() {
+        let number = game.height - row;
+        let squares: Vec<String> = squares.iter()
+            .map(|&piece| if piece == ' ' { ".".to_string() } else { piece.to_string() })
+            .collect();
+        board_state += &format!("  {:>width$} {} {}\n", number, squares.join(" "), number, width = row_width);
     }
+    board_state + &header
+}
 
-    fn display_indexed_board(&mut self) {
-        // Displays board with index repreesentations for placing pieces
-        let mut total_lines = &SIZE - 1;
-        for row in &BOARD_IDX {
-            println!("  {} | {} | {}", row[0], row[1], row[2]);
-            if total_lines > 0 {
-                println!(" -----------");
-                total_lines -= 1;
+fn display_game(game: &Game) {
+    // Displays the game after each move, labelling the rows and columns of boards that use notation
+    match uses_notation(game) {
+        true => {
+            let status = match game.end_game {
+                true => "ended".to_string(),
+                false => format!("in play, {}'s turn", game.players[game.curr_player]),
+            };
+            println!("\nGame {}:\n{}", status, coordinate_board(game));
+        },
+        false => println!("{}", game),
+    }
+}
+
+fn read_response() -> String {
+    // Grabs the user's response from stdin
+    let mut stdout = stdout();
+    let stdin = stdin();
+    if let Err(error) = stdout.flush() {
+        panic!("Unable to flush buffer, {}", error);
+    }
+    let mut user_response = String::with_capacity(100);
+    stdin.read_line(&mut user_response).unwrap();
+    user_response
+}
+
+fn get_user_input(game: &Game) -> Command {
+    // Grabs the user's move from stdin and checks for validity
+    let user_response = read_response();
+
+    // Check that the input was a location on the board or a command
+    match parse_command(&user_response, game) {
+        Some(command) => {
+            match command {
+                Command::Place(loc) if game.gravity => println!("You entered: column {}", loc % game.width + 1),
+                Command::Place(loc) => println!("You entered: {}", square_name(game, loc)),
+                Command::PlacePiece(loc, symbol) => {
+                    println!("You entered: {} {}", square_name(game, loc), game.players[symbol])
+                },
+                _ => (),
             }
-        }
-        println!("\n");
+            command
+        },
+        None => {
+            println!("\nPlease enter a valid response: ");
+            get_user_input(game)
+        },
     }
+}
 
-    fn get_user_input(&mut self) -> usize {
-        // Grabs the user's move from stdin and checks for validity
-        let mut stdout = stdout();
-        let stdin = stdin();
-        let _clean = match stdout.flush() {
-            Ok(result) => result,
-            Err(error) => panic!("Unable to flush buffer, {}", error),
-        };
+fn parse_command(user_response: &str, game: &Game) -> Option<Command> {
+    // Returns the command typed by the user: `u` to undo, `r` to redo, `h` for a hint, or a location on the board,
+    // which in wild games may be followed by the piece to place there
+    let parse_square = if game.gravity { parse_column } else { parse_location };
+    match user_response.trim() {
+        "u" | "undo" => Some(Command::Undo),
+        "r" | "redo" => Some(Command::Redo),
+        "h" | "hint" => Some(Command::Hint),
+        response => match parse_piece(response, game) {
+            Some((response, symbol)) => parse_square(response, game).map(|loc| Command::PlacePiece(loc, symbol)),
+            None => parse_square(response, game).map(Command::Place),
+        },
+    }
+}
 
-        // Grab user's response
-        let mut user_response = String::with_capacity(100);
-        stdin.read_line(&mut user_response).unwrap();
+fn parse_piece<'a>(user_response: &'a str, game: &Game) -> Option<(&'a str, usize)> {
+    // Splits a piece, e.g. the `O` of `4 O`, off the end of the user's response in a wild game,
+    // returning the rest of the response and the piece (index into `players`), ignoring case
+    if !game.wild {
+        return None;
+    }
+    let response = user_response.trim();
+    let last = response.chars().last()?.to_ascii_uppercase();
+    let symbol = game.players.iter().position(|&piece| piece == last)?;
+    Some((&response[..response.len() - 1], symbol))
+}
 
-        // Check that the input was a valid character
-        let mut first_char = user_response.chars().next().unwrap();
-        match &mut first_char {
-            '0' ... '8' => {
-                println!("You entered: {}", first_char);
-                first_char.to_digit(10).unwrap() as usize
-            },
-            _ => { 
-                println!("\nPlease enter a valid response: ");
-                self.get_user_input()
-            },
-        }
+fn parse_location(user_response: &str, game: &Game) -> Option<usize> {
+    // Returns the location (index) typed by the user if it is on the board, given either as the
+    // location itself, as `row,column` (`layer,row,column` on a layered board) counting from 1,
+    // or by column letter and row number such as `h8`
+    if let Some(loc) = game.parse_notation(user_response) {
+        return Some(loc);
     }
+    let numbers: Vec<usize> = user_response.trim().split(',').map(|part| part.trim().parse().ok()).collect::<Option<_>>()?;
+    match (numbers.as_slice(), game.layers) {
+        (&[loc], _) if loc < game.total_squares() => Some(loc),
+        (&[row, col], 1) if row > 0 && col > 0 => game.location(0, row - 1, col - 1),
+        (&[layer, row, col], layers) if layers > 1 && layer > 0 && row > 0 && col > 0 => {
+            game.location(layer - 1, row - 1, col - 1)
+        },
+        _ => None,
+    }
+}
 
-    fn is_endgame(&mut self) -> bool {
-        // Checks for end game win/draw states returning True if an endgame is reached, False otherwise
-        let total_states = SIZE + SIZE + 2;
-        let slices = [[0, 1, 2], [3, 4, 5], [6, 7, 8],
-                      [0, 3, 6], [1, 4, 7], [2, 5, 8],
-                      [0, 4, 8], [2, 4, 6]];
-        let mut board_slice: Vec<char> = vec![];
-
-        // Pass list of arrays as rows and loop over them to check for win state
-        for slice in 0..total_states {
-            // e.g. [0, 1, 2]
-            let state = &slices[slice];
-
-            for index in 0..SIZE {
-                // e.g. 0
-                let loc = state[index];
-                let x = self.coordinates[loc].x;
-                let y = self.coordinates[loc].y;
-                board_slice.push(self.board[x][y])
-            }
+fn parse_column(user_response: &str, game: &Game) -> Option<usize> {
+    // Returns the location (index) a piece dropped into the column typed by the user would land on,
+    // counting columns from 1, or None if the column is full or not on the board
+    match user_response.trim().parse::<usize>() {
+        Ok(column) if column > 0 => game.drop_location(column - 1),
+        _ => None,
+    }
+}
 
-            // Check the current slice of the board for a winning state
-            if self.is_win(&board_slice) == true {
-                board_slice.clear();
-                return true;
+fn declare_winner(game: &Game) {
+    // Declares a winner, or a draw when nobody won
+    match game.outcome {
+        Outcome::Win { player, ref line } => {
+            let squares: Vec<String> = line.iter().map(|&loc| square_name(game, loc)).collect();
+            match game.rules {
+                Rules::Standard => println!("\nWINNER: Player {} won the game on squares {}!",
+                                            game.players[player], squares.join(", ")),
+                Rules::Misere => println!("\nWINNER: Player {} won the game when Player {} completed squares {}!",
+                                          game.players[player], game.players[1 - player], squares.join(", ")),
             }
-            board_slice.clear();
-        }
+        },
+        Outcome::Draw => println!("\nDRAW: nobody wins"),
+        Outcome::InProgress => (),
+    }
+}
 
-        // if the board is full, check for drawn state
-        self.is_draw()
+fn run_ultimate(options: &Options) {
+    // Play ultimate tic-tac-toe until an endgame state is reached; automatic players move at random
+    let mut game = Ultimate::new();
+    if let Some(seed) = options.seed {
+        game.set_seed(seed);
     }
+    println!("\nSeed :: {} (replay this game with `--ultimate --seed {}`)", game.seed(), game.seed());
+    let auto = [P1_PLAY, P2_PLAY];
+    let name = |auto: bool| if auto { "random" } else { "manual" };
+    println!("\nPlayer 1 :: {} ({} play)\nPlayer 2 :: {} ({} play)\n", P1, name(auto[0]), P2, name(auto[1]));
+    println!("{}", game);
 
-    fn is_draw(&mut self) -> bool {
-        // Checks for drawn states and returns True if a drawn state is reached, False otherwise
-        let mut is_drawn = true;
-        for row in self.board.iter() {
-            is_drawn = match row {
-                [' ', _, _] | [_, ' ', _] | [_, _, ' '] => false,  // board is not full
-                _ => true,                                         // board is full
-            };
-            if !is_drawn {
-                return false;
-            }
+    while !game.end_game {
+        match auto[game.curr_player] {
+            true => {
+                let loc = game.random_move().expect("a game in play has a legal move");
+                game.place(loc).expect("random moves are always legal");
+            },
+            false => manual_ultimate_move(&mut game),
         }
-        is_drawn
+        println!("{}", game);
     }
+    declare_ultimate_winner(&game);
+}
 
-    fn is_win(&mut self, row: &Vec<char>) -> bool {
-        // Checks for win states and returns True if a win state is reached, False otherwise
-        if row == &self.win_states.p1_win_state {
-            self.winner = 0;
-            self.declare_winner();
-            return true;
+fn manual_ultimate_move(game: &mut Ultimate) {
+    // Ask the user for the location where they want to place their piece until the engine accepts it
+    println!("\nWhere do you want to place your piece? (a number or `board,square` counting from 1)");
+    print!("{}\n\n", ultimate_board(game));
+    loop {
+        let response = read_response();
+        let loc = match parse_ultimate_location(&response, game) {
+            Some(loc) => loc,
+            None => {
+                println!("\nPlease enter a valid response: ");
+                continue;
+            },
+        };
+        match game.place(loc) {
+            Ok(_) => return,
+            Err(error) => println!("\n{}. Please enter a valid location: ", error),
         }
-        if row == &self.win_states.p2_win_state {
-            self.winner = 1;
-            self.declare_winner();
-            return true;
+    }
+}
+
+fn ultimate_board(game: &Ultimate) -> String {
+    // Builds the whole grid with each playable square labelled by its location (index)
+    // and every other square showing its piece
+    let labels: Vec<String> = (0..SQUARES)
+        .map(|loc| match game.is_legal(loc) {
+            true => loc.to_string(),
+            false => game.piece_at(loc).to_string(),
+        })
+        .collect();
+    let cell_width = index_width(SQUARES);
+    let small_divider = vec!["-".repeat((cell_width + 3) * SIZE - 1); SIZE].join("-||-");
+    let meta_divider = "=".repeat(small_divider.len());
+    let mut board_state = String::new();
+    for row in 0..SIDE {
+        let boards: Vec<String> = (0..SIZE)
+            .map(|board_col| {
+                let squares: Vec<String> = (0..SIZE)
+                    .map(|col| format!("{:>width$}", labels[Ultimate::grid_location(row, board_col * SIZE + col)],
+                                       width = cell_width))
+                    .collect();
+                squares.join(" | ")
+            })
+            .collect();
+        board_state += &format!("  {}\n", boards.join("  ||  "));
+        if row + 1 < SIDE {
+            let divider = if (row + 1) % SIZE == 0 { &meta_divider } else { &small_divider };
+            board_state += &format!(" {}\n", divider);
         }
-        false
     }
+    board_state
+}
 
-    fn declare_winner(&mut self) {
-        // Declares a winner
-        println!("\nWINNER: Player {} won the game!", self.players[self.winner]);
+fn parse_ultimate_location(user_response: &str, game: &Ultimate) -> Option<usize> {
+    // Returns the location (index) typed by the user if it is on the grid, given either as the
+    // location itself or as `board,square` counting from 1, with boards and squares numbered like the locations of 3x3
+    let numbers: Vec<usize> = user_response.trim().split(',').map(|part| part.trim().parse().ok()).collect::<Option<_>>()?;
+    match *numbers.as_slice() {
+        [loc] if loc < SQUARES => Some(loc),
+        [board, square] if board > 0 && square > 0 => game.location(board - 1, square - 1),
+        _ => None,
     }
+}
 
-    fn reset(&mut self) {
-        // Reset Game
-        self.board = [[' ', ' ', ' '], 
-                     [' ', ' ', ' '], 
-                     [' ', ' ', ' ']];
-        self.curr_player = 0;
-        self.end_game = false;
-        self.coordinates.clear();
-        self.coordinates = coord_mapping();
-        self.winner = NO_WIN;
+fn declare_ultimate_winner(game: &Ultimate) {
+    // Declares the winner of the meta-board, or a draw when nobody won
+    match game.outcome {
+        Outcome::Win { player, ref line } => {
+            let boards: Vec<String> = line.iter().map(|board| (board + 1).to_string()).collect();
+            println!("\nWINNER: Player {} won the game on boards {}!", game.players[player], boards.join(", "));
+        },
+        Outcome::Draw => println!("\nDRAW: nobody wins"),
+        Outcome::InProgress => (),
     }
 }
 
-fn coord_mapping() -> Vec<Coord> {
-    // Generates a coordinate mapping of vector indices -> coordinates on the board
-    // and `legal` represents whether a square is available for placing a piece (True)
-    // or already has a piece placed on it (False)
-    let mut coordinates: Vec<Coord> = vec![];
-    for i in 0..SIZE {
-        for j in 0..SIZE {
-            let coord = Coord { x: i, y: j, legal: true };
-            coordinates.push(coord);
+fn run_notakto(options: &Options) {
+    // Play notakto until the last board dies; automatic players follow the misère quotient at the `--level` difficulty
+    let mut game = Notakto::new(options.boards);
+    if let Some(seed) = options.seed {
+        game.set_seed(seed);
+    }
+    println!("\nRules :: notakto (both players place {}; a board with three in a row is dead, \
+              and whoever kills the last board loses)", P1);
+    println!("\nSeed :: {} (replay this game with `--notakto --boards {} --seed {}`)",
+             game.seed(), options.boards, game.seed());
+    let auto = [P1_PLAY, P2_PLAY];
+    let name = |auto: bool| if auto { options.difficulty.to_string() } else { "manual".to_string() };
+    println!("\nPlayer 1 :: {} play\nPlayer 2 :: {} play", name(auto[0]), name(auto[1]));
+    println!("{}", game);
+
+    while !game.end_game {
+        match auto[game.curr_player] {
+            true => {
+                let loc = game.choose_move(options.difficulty).expect("a game in play has a legal move");
+                game.place(loc).expect("automatic moves are always legal");
+            },
+            false => manual_notakto_move(&mut game),
         }
+        println!("{}", game);
     }
-    coordinates
+    declare_notakto_winner(&game);
 }
 
-#[allow(unused_must_use)]
-impl fmt::Display for Game {
-    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-        //  Display game state (allows display with macros like println!)
-        let mut game_status = format!("in play, {}'s turn", &self.players[self.curr_player]);
-        if self.end_game == true {
-            game_status = "ended".to_string();
+fn manual_notakto_move(game: &mut Notakto) {
+    // Ask the user for the location where they want to place an X until the engine accepts it,
+    // or whether to see a hint
+    println!("\nWhere do you want to place an {}? (a number or `board,square` counting from 1, or `h` for a hint)", P1);
+    print!("{}\n\n", notakto_board(game));
+    loop {
+        let response = read_response();
+        if matches!(response.trim(), "h" | "hint") {
+            println!("\n{}\nWhere do you want to place an {}? ", notakto_hint(game), P1);
+            continue;
         }
+        let loc = match parse_notakto_location(&response, game) {
+            Some(loc) => loc,
+            None => {
+                println!("\nPlease enter a valid response: ");
+                continue;
+            },
+        };
+        match game.place(loc) {
+            Ok(_) => return,
+            Err(MoveError::WrongBoard(_)) => println!("\nThat board is dead. Please enter a valid location: "),
+            Err(error) => println!("\n{}. Please enter a valid location: ", error),
+        }
+    }
+}
 
-        let mut total_lines = &SIZE - 1;
-        write!(formatter, "\nGame {}:\n", &game_status);
-        for row in &self.board {
-            write!(formatter, "  {} | {} | {}\n", row[0], row[1], row[2]);
-            if total_lines > 0 {
-                write!(formatter, " -----------\n");
-                total_lines -= 1;
-            }
+fn notakto_board(game: &Notakto) -> String {
+    // Builds the boards side by side with each playable square labelled by its location (index)
+    // and every other square showing its piece
+    let cell_width = index_width(game.total_squares());
+    let label = |loc: usize| match game.is_legal(loc) {
+        true => format!("{:>width$}", loc, width = cell_width),
+        false => format!("{:>width$}", game.piece_at(loc), width = cell_width),
+    };
+    let divider = vec!["-".repeat((cell_width + 3) * SIZE - 1); game.boards.len()].join("-||-");
+    let mut board_state = String::new();
+    for row in 0..SIZE {
+        let boards: Vec<String> = (0..game.boards.len())
+            .map(|board| {
+                let squares: Vec<String> = (0..SIZE).map(|col| label(board * notakto::SQUARES + row * SIZE + col)).collect();
+                squares.join(" | ")
+            })
+            .collect();
+        board_state += &format!("  {}\n", boards.join("  ||  "));
+        if row + 1 < SIZE {
+            board_state += &format!(" {}\n", divider);
         }
-        Ok(())
+    }
+    board_state
+}
+
+fn notakto_hint(game: &Notakto) -> String {
+    // Describes the game's element of the misère quotient and the moves that win against any reply
+    let moves: Vec<String> = game.winning_moves().iter().map(usize::to_string).collect();
+    match moves.is_empty() {
+        true => format!("Value {} :: every move loses against perfect play", game.value()),
+        false => format!("Value {} :: winning moves {}", game.value(), moves.join(", ")),
+    }
+}
+
+fn parse_notakto_location(user_response: &str, game: &Notakto) -> Option<usize> {
+    // Returns the location (index) typed by the user if it is on the boards, given either as the
+    // location itself or as `board,square` counting from 1
+    let numbers: Vec<usize> = user_response.trim().split(',').map(|part| part.trim().parse().ok()).collect::<Option<_>>()?;
+    match *numbers.as_slice() {
+        [loc] if loc < game.total_squares() => Some(loc),
+        [board, square] if board > 0 && square > 0 => game.location(board - 1, square - 1),
+        _ => None,
+    }
+}
+
+fn declare_notakto_winner(game: &Notakto) {
+    // Declares the winner, who did not kill the last board
+    if let Outcome::Win { player, ref line } = game.outcome {
+        let squares: Vec<String> = line.iter().map(usize::to_string).collect();
+        println!("\nWINNER: Player {} won the game when Player {} completed squares {} on the last board!",
+                 player + 1, 2 - player, squares.join(", "));
     }
 }
 
 fn main() {
     // Play the game until an endgame state is reached
-    let mut game = Game::new();
-    game.start(P1_PLAY, P2_PLAY);
-    println!("{}", game);
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("solve") {
+        return run_solve(&args[1..]);
+    }
+    let options = match parse_options(&args) {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("{}\n{}", error, USAGE);
+            std::process::exit(2);
+        },
+    };
+    match options.variant {
+        Variant::Standard => (),
+        Variant::Ultimate => return run_ultimate(&options),
+        Variant::Notakto => return run_notakto(&options),
+    }
+    let mut game = new_game(&options);
+    if options.rules == Rules::Misere {
+        println!("\nRules :: misère (completing a line loses)");
+    }
+    if options.wild {
+        println!("\nRules :: wild (either player may place {} or {}, and completing a line counts for whoever completed it)", P1, P2);
+    }
+    if options.gravity {
+        println!("\nRules :: gravity (pieces drop to the lowest open square of their column)");
+    }
+    match options.restriction {
+        Restriction::Freestyle => (),
+        Restriction::Exact => println!("\nRules :: exactly {} in a row wins, longer lines do not", options.win_length),
+        Restriction::Renju => println!("\nRules :: renju (no overlines, double fours or double threes for {})", P1),
+    }
+    println!("\nSeed :: {} (replay this game with `--seed {}`)", game.seed(), game.seed());
+    let mut players = start(&options, P1_PLAY, P2_PLAY);
+    display_game(&game);
 
-    while game.end_game == false {
-        game.update();
-        println!("{}", game);
+    while !game.end_game {
+        update(&mut game, &mut players);
+        display_game(&game);
     }
+    declare_winner(&game);
     game.reset();
 }
 
@@ -341,275 +850,308 @@ fn main() {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use more_asserts::{assert_ge};
-
-    #[test]
-    fn test_board_init() {
-        // Test that init board contains correct values
-        let board: [[char; SIZE]; SIZE];
-        board = [[' ', ' ', ' '], 
-                [' ', ' ', ' '], 
-                [' ', ' ', ' ']];
-        let game = Game::new();
-        assert_eq!(board, game.board);
-    }
 
     #[test]
     fn test_start() {
-        // Test that players are set with correct autoplay booleans
-        let mut game = Game::new();
-        game.start(true, true);
-        assert_eq!(AutoPlay { play_type: [true, true], 
-                              play_type_str: ["automatic".to_string(), "automatic".to_string()] }, 
-                              game.auto_play);
-        game.start(false, true);
-        assert_eq!(AutoPlay { play_type: [false, true], 
-                              play_type_str: ["manual".to_string(), "automatic".to_string()] }, 
-                              game.auto_play);
-        game.start(true, false);
-        assert_eq!(AutoPlay { play_type: [true, false], 
-                              play_type_str: ["automatic".to_string(), "manual".to_string()] }, 
-                              game.auto_play);
-        game.start(false, false);
-        assert_eq!(AutoPlay { play_type: [false, false], 
-                              play_type_str: ["manual".to_string(), "manual".to_string()] }, 
-                              game.auto_play);
+        // Test that starting from the CLI builds players from the options
+        let options = Options { difficulty: Difficulty::Hard, ..Options::default() };
+        let players = start(&options, false, true);
+        assert_eq!(players[0].name(), "manual");
+        assert_eq!(players[1].name(), "hard");
     }
 
     #[test]
-    fn test_game_display() {
-        // Test that init board displays correctly
+    fn test_update_auto_players() {
+        // Tests that automatic players fill the board until an endgame is reached
         let mut game = Game::new();
-        game.start(true, true);
+        game.set_seed(3);
+        let options = Options { difficulty: Difficulty::Medium, ..Options::default() };
+        let mut players = start(&options, true, true);
 
-        let mut game_status = format!("Game in play, {}'s turn", &game.players[game.curr_player]);
-        if game.end_game {
-            game_status = "Game ended".to_string();
+        let mut max_moves = SIZE * SIZE;
+        while !game.end_game && max_moves > 0 {
+            update(&mut game, &mut players);
+            max_moves -= 1;
         }
-        let mut expect_board = format!("\n{}:", &game_status);
-        expect_board += "\n    |   |  \n -----------\n    |   |  \n -----------\n    |   |  \n";
-        assert_eq!(expect_board, format!("{}", game));
+        assert!(game.end_game);
     }
 
     #[test]
-    fn test_auto_move_reaches_endgame() {
-        // Should result in an endgame within Game const `SIZE * SIZE` moves, 
-        // otherwise, there is a halting error, which is caught by the while loop's
-        // `max_moves > -2` stopping condition. If this stopping condition is used
-        // the assert will fail.
-        let mut max_moves = (SIZE * SIZE) as isize;
-        let mut game = Game::new();
-
-        game.start(true, true);
-
-        while (game.end_game == false) && (max_moves > -2) {
-            println!("max_moves: {:?}, end_game: {:?}", max_moves, game.end_game);
-            game.update();
-            max_moves -= 1;
-        }
-        println!("exited while loop");
-        println!("max_moves: {:?}, end_game: {:?}", max_moves, game.end_game);
-        assert_ge!(max_moves, 0)
+    fn test_parse_options() {
+        // Tests that board flags are read and invalid flags are rejected
+        let args = |line: &str| -> Vec<String> { line.split_whitespace().map(String::from).collect() };
+        assert_eq!(parse_options(&args("")), Ok(Options::default()));
+        assert_eq!(parse_options(&args("--width 7 --height 6 --win 4")),
+                   Ok(Options { width: 7, height: 6, win_length: 4, ..Options::default() }));
+        assert_eq!(parse_options(&args("--ai minimax")).map(|options| options.strategy), Ok(Strategy::Minimax));
+        assert_eq!(parse_options(&args("--ai mcts")).map(|options| options.strategy), Ok(Strategy::Mcts));
+        assert!(parse_options(&args("--ai minimax --level perfect")).is_ok());
+        assert!(parse_options(&args("--ai minimax --level perfect --width 4 --height 4")).is_err());
+        assert!(parse_options(&args("--gomoku --ai minimax --level medium")).is_ok());
+        assert!(parse_options(&args("--gomoku --ai minimax --level hard")).is_err());
+        assert!(parse_options(&args("--ai clever")).is_err());
+        assert_eq!(parse_options(&args("--level hard")).map(|options| options.difficulty), Ok(Difficulty::Hard));
+        assert!(parse_options(&args("--level impossible")).is_err());
+        assert_eq!(parse_options(&args("--seed 0")).map(|options| options.seed), Ok(Some(0)));
+        assert_eq!(parse_options(&args("--misere --level easy")),
+                   Ok(Options { rules: Rules::Misere, difficulty: Difficulty::Easy, ..Options::default() }));
+        assert!(parse_options(&args("--seed -1")).is_err());
+        assert!(parse_options(&args("--width")).is_err());
+        assert!(parse_options(&args("--width 0")).is_err());
+        assert!(parse_options(&args("--depth 3")).is_err());
+        assert!(parse_options(&args("--win 4")).is_err());
+        assert!(parse_options(&args("--width 17 --height 16")).is_err());
+        assert_eq!(parse_options(&args("--width 4 --height 4 --layers 4 --win 4")),
+                   Ok(Options { width: 4, height: 4, layers: 4, win_length: 4, ..Options::default() }));
+        assert!(parse_options(&args("--layers 2 --width 8 --height 8 --win 4")).is_ok());
+        assert!(parse_options(&args("--layers 5 --width 8 --height 8")).is_err());
+        assert!(parse_options(&args("--layers 2 --width 2 --height 2 --win 3")).is_err());
     }
 
     #[test]
-    fn test_is_win_false() {
-        // Tests that the function returns a `false` to signify a win has not occurred
-        // for various scenarios
-        let mut game = Game::new();
-
-        let mut test_row: Vec<char> = vec![P1, P2, P2];
-        assert_ne!(game.is_win(&test_row), true);
-        test_row.clear();
-
-        test_row = vec![P1, P1, P2];
-        assert_ne!(game.is_win(&test_row), true);
-        test_row.clear();
-
-        test_row = vec![P2, P1, P2];
-        assert_ne!(game.is_win(&test_row), true);
-        test_row.clear();
-
-        test_row = vec![' ', P1, P1];
-        assert_ne!(game.is_win(&test_row), true);
-        test_row.clear();
-
-        test_row = vec![P1, ' ', P1];
-        assert_ne!(game.is_win(&test_row), true);
-        test_row.clear();
-
-        test_row = vec![P2, P2, ' '];
-        assert_ne!(game.is_win(&test_row), true);
+    fn test_mcts_flags() {
+        // Tests that the MCTS flags tune the automatic player's search and need `--ai mcts`
+        let args = |line: &str| -> Vec<String> { line.split_whitespace().map(String::from).collect() };
+        let options = parse_options(&args("--ai mcts --level hard --playouts 50 --exploration 0.5")).unwrap();
+        assert_eq!((options.playouts, options.exploration), (Some(50), Some(0.5)));
+        let config = ai_player(&options).mcts.unwrap();
+        assert_eq!((config.iterations, config.max_time, config.exploration), (Some(50), None, 0.5));
+        let timed = ai_player(&parse_options(&args("--ai mcts --mcts-time 20")).unwrap()).mcts.unwrap();
+        assert_eq!((timed.iterations, timed.max_time), (None, Some(Duration::from_millis(20))));
+        assert_eq!(ai_player(&parse_options(&args("--ai mcts --level easy")).unwrap()).mcts.unwrap().iterations, Some(100));
+        assert!(parse_options(&args("--playouts 50")).is_err());
+        assert!(parse_options(&args("--ai mcts --playouts 0")).is_err());
+        assert!(parse_options(&args("--ai mcts --exploration -1")).is_err());
+        assert!(parse_options(&args("--ai mcts --exploration fast")).is_err());
     }
 
     #[test]
-    fn test_is_win_true() {
-        // Tests that the function returns a `true` to signify a win has occurred
-        let mut game = Game::new();
-
-        let mut test_row: Vec<char> = vec![P1, P1, P1];
-        assert_eq!(game.is_win(&test_row), true);
-        test_row.clear();
-
-        test_row = vec![P2, P2, P2];
-        assert_eq!(game.is_win(&test_row), true);
-        test_row.clear();
+    fn test_gomoku() {
+        // Tests the gomoku board and restriction flags, the labelled board and square names like `h8`
+        let args = |line: &str| -> Vec<String> { line.split_whitespace().map(String::from).collect() };
+        assert_eq!(parse_options(&args("--gomoku --renju")),
+                   Ok(Options { width: 15, height: 15, win_length: 5, restriction: Restriction::Renju, ..Options::default() }));
+        assert_eq!(parse_options(&args("--exact")).map(|options| options.restriction), Ok(Restriction::Exact));
+        let mut game = Game::with_size(15, 15, 5);
+        assert_eq!(parse_location("h8\n", &game), Some(112));
+        assert_eq!(parse_location("A15\n", &game), Some(0));
+        assert_eq!(parse_location("8,8\n", &game), Some(112));
+        assert_eq!(parse_location("a1\n", &Game::new()), Some(6));
+        assert_eq!(parse_location("q1\n", &game), None);
+        game.place(112).unwrap();
+        assert_eq!(square_name(&game, 112), "h8");
+        assert_eq!(square_name(&Game::new(), 4), "4");
+        let lines: Vec<String> = coordinate_board(&game).lines().map(String::from).collect();
+        assert_eq!(lines.len(), 17);
+        assert_eq!(lines[0], "     a b c d e f g h i j k l m n o");
+        assert_eq!(lines[1], "  15 . . . . . . . . . . . . . . . 15");
+        assert_eq!(lines[8], "   8 . . . . . . . X . . . . . . . 8");
     }
 
     #[test]
-    fn test_is_win_none() {
-        // Test for correct default value when there is no winner
-        let mut game = Game::new();
-        let test_row: Vec<char> = vec![' ', P1, ' '];
-        let _game_won = game.is_win(&test_row);
-        assert_eq!(game.winner, NO_WIN);
+    fn test_connect_four() {
+        // Tests the gravity flags, column input and the board with column numbers
+        let args = |line: &str| -> Vec<String> { line.split_whitespace().map(String::from).collect() };
+        assert_eq!(parse_options(&args("--connect-four")),
+                   Ok(Options { width: 7, height: 6, win_length: 4, gravity: true, ..Options::default() }));
+        assert_eq!(parse_options(&args("--gravity --width 4 --height 4")).map(|options| options.gravity), Ok(true));
+        assert!(parse_options(&args("--gravity --layers 3")).is_err());
+        let mut game = Game::with_size(7, 6, 4);
+        game.set_gravity(true);
+        assert_eq!(parse_command("4\n", &game), Some(Command::Place(38)));
+        assert_eq!(parse_command("8\n", &game), None);
+        assert_eq!(parse_command("0\n", &game), None);
+        game.drop(3).unwrap();
+        assert_eq!(parse_command("4\n", &game), Some(Command::Place(31)));
+        let lines: Vec<String> = column_board(&game).lines().map(String::from).collect();
+        assert_eq!(lines.len(), 12);
+        assert_eq!(lines[10], "    |   |   | X |   |   |  ");
+        assert_eq!(lines[11], "  1   2   3   4   5   6   7");
+        assert!(solve_position(&Options { gravity: true, ..Options::default() }, &[0]).is_err());
     }
 
     #[test]
-    fn test_p2_win_state() {
-        // Test for correct winner when P2 wins
+    fn test_wild() {
+        // Tests the wild flag and typing a piece after the location
+        let args = |line: &str| -> Vec<String> { line.split_whitespace().map(String::from).collect() };
+        assert_eq!(parse_options(&args("--wild")).map(|options| options.wild), Ok(true));
+        assert!(parse_options(&args("--wild --level random --ai mcts")).is_ok());
+        assert!(parse_options(&args("--wild --level easy")).is_err());
         let mut game = Game::new();
-        let test_row: Vec<char> = vec![P2, P2, P2];
-        let _game_won = game.is_win(&test_row);
-        assert_eq!(game.players[game.winner], P2);
+        assert_eq!(parse_command("4 o\n", &game), None);
+        game.set_wild(true);
+        assert_eq!(parse_command("4 O\n", &game), Some(Command::PlacePiece(4, 1)));
+        assert_eq!(parse_command("2,3x\n", &game), Some(Command::PlacePiece(5, 0)));
+        assert_eq!(parse_command("4\n", &game), Some(Command::Place(4)));
+        assert_eq!(parse_command("9 O\n", &game), None);
+        assert_eq!(parse_command("O\n", &game), None);
+        assert_eq!(parse_command("undo\n", &game), Some(Command::Undo));
+        assert!(solve_position(&Options { wild: true, ..Options::default() }, &[]).is_err());
     }
 
     #[test]
-    fn test_p1_win_state() {
-        // Test for correct winner when P1 wins
-        let mut game = Game::new();
-        let test_row: Vec<char> = vec![P1, P1, P1];
-        let _game_won = game.is_win(&test_row);
-        assert_eq!(game.players[game.winner], P1);
+    fn test_parse_solve_args() {
+        // Tests that the solve subcommand reads its moves alongside the board flags
+        let args = |line: &str| -> Vec<String> { line.split_whitespace().map(String::from).collect() };
+        assert_eq!(parse_solve_args(&args("")), Ok((Options::default(), vec![])));
+        assert_eq!(parse_solve_args(&args("--width 4 --moves 0,5 --height 4")),
+                   Ok((Options { width: 4, height: 4, ..Options::default() }, vec![0, 5])));
+        assert!(parse_solve_args(&args("--moves")).is_err());
+        assert!(parse_solve_args(&args("--moves 0,x")).is_err());
+        assert!(solve_position(&Options::default(), &[4, 4]).is_err());
+        assert!(solve_position(&Options::default(), &[0, 3, 1, 4, 2, 5]).is_err());
+        let misere = Options { rules: Rules::Misere, ..Options::default() };
+        assert_eq!(solve_position(&misere, &[]).map(|position| position.rules()), Ok(Rules::Misere));
+        let gomoku = Options { width: 15, height: 15, win_length: 5, ..Options::default() };
+        assert_eq!(solve_position(&gomoku, &[]),
+                   Err("a 15x15 board has too many open squares to solve, so play more --moves to leave at most 13".to_string()));
+        let four = Options { width: 4, height: 4, win_length: 4, ..Options::default() };
+        assert!(solve_position(&four, &[0, 5]).is_err());
+        assert!(solve_position(&four, &[0, 5, 10]).is_ok());
     }
 
-
     #[test]
-    fn test_declare_winner_p1() {
-        // Test that the correct winner was declared 
-        // by testing the printed variable's value (left-hand assert_eq param)
-        // with the expected value (right-hand assert_eq param)
-        let mut game = Game::new();
-        let test_row: Vec<char> = vec![P1, P1, P1];
-        let _game_won = game.is_win(&test_row);
-        game.declare_winner();
-        assert_eq!(game.players[game.winner], P1);
+    fn test_solve_report() {
+        // Tests that the report counts games for the player to move and lists every move
+        let position = solve_position(&Options::default(), &[0, 1]).unwrap();
+        let report = solve_report(&position, &solve(&position));
+        assert!(report.starts_with("X to move :: win\n"));
+        assert!(report.contains("    4 |   win |"));
+        assert_eq!(report.lines().count(), 3 + 2 + 7);
     }
 
     #[test]
-    fn test_declare_winner_p2() {
-        // Test that the correct winner was declared 
-        // by testing the printed variable's value (left-hand assert_eq param)
-        // with the expected value (right-hand assert_eq param)
-        let mut game = Game::new();
-        let test_row: Vec<char> = vec![P2, P2, P2];
-        let _game_won = game.is_win(&test_row);
-        game.declare_winner();
-        assert_eq!(game.players[game.winner], P2);
+    fn test_indexed_board() {
+        // Tests that index labels are padded to the widest index on larger boards
+        assert_eq!(indexed_board(&Game::new()),
+                   "  0 | 1 | 2\n -----------\n  3 | 4 | 5\n -----------\n  6 | 7 | 8\n");
+        assert_eq!(indexed_board(&Game::with_size(4, 3, 3)),
+                   "   0 |  1 |  2 |  3\n -------------------\n   4 |  5 |  6 |  7\n -------------------\n   8 |  9 | 10 | 11\n");
+        assert_eq!(indexed_board(&Game::with_layers(2, 2, 2, 2)),
+                   "  Layer 1\n  0 | 1\n -------\n  2 | 3\n\n  Layer 2\n  4 | 5\n -------\n  6 | 7\n");
     }
 
     #[test]
-    fn test_reset() {
-        // Tests if the game resets correctly to its original values after being played
-        // by comparing with another unplayed game instantiated with the same initial values
-        let mut original_game = Game::new();
-        original_game.start(true, true);
-
-        while original_game.end_game == false {
-            original_game.update();
-            println!("{}", original_game);
+    fn test_hint_board() {
+        // Tests that open squares show their hint and occupied squares their piece
+        let mut game = Game::new();
+        for &loc in &[0, 3, 1, 4] {
+            game.place(loc).unwrap();
         }
-        original_game.reset();
-
-        let mut comparison_game = Game::new();
-        comparison_game.start(true, true);
-        assert_eq!(original_game, comparison_game);
+        assert_eq!(hint_board(&game, &evaluate(&game).unwrap()),
+                   "   X |  X | W1\n --------------\n   O |  O |  D\n --------------\n  L2 | L2 | L2\n");
     }
 
     #[test]
-    fn test_is_draw_true() {
-        // Tests that the function returns a `true` to signify a draw has occurred
-        let mut game = Game::new();
-
-        game.board = [['O', 'X', 'O'],
-                      ['O', 'X', 'X'],
-                      ['X', 'O', 'X']];
-        assert_eq!(game.is_draw(), true);
-
-        game.board = [['X', 'X', 'O'],
-                      ['O', 'O', 'X'],
-                      ['X', 'X', 'O']];
-        assert_eq!(game.is_draw(), true);
+    fn test_parse_location() {
+        // Tests that multi-digit locations are accepted only when they are on the board
+        assert_eq!(parse_location("8\n", &Game::new()), Some(8));
+        assert_eq!(parse_location("15\n", &Game::with_size(4, 4, 4)), Some(15));
+        assert_eq!(parse_location("9\n", &Game::new()), None);
+        assert_eq!(parse_location("x\n", &Game::new()), None);
     }
 
     #[test]
-    fn test_is_draw_false() {
-        // Tests that the function returns a `true` to signify a draw has occurred
-        let mut game = Game::new();
+    fn test_parse_location_coordinates() {
+        // Tests that squares can be named by row and column, plus layer on a layered board, counting from 1
+        let qubic = Game::with_layers(4, 4, 4, 4);
+        assert_eq!(parse_location("2,3\n", &Game::new()), Some(5));
+        assert_eq!(parse_location("4, 2, 3\n", &qubic), Some(54));
+        assert_eq!(parse_location("63\n", &qubic), Some(63));
+        assert_eq!(parse_location("2,3\n", &qubic), None);
+        assert_eq!(parse_location("1,2,3\n", &Game::new()), None);
+        assert_eq!(parse_location("0,1\n", &Game::new()), None);
+        assert_eq!(parse_location("5,1,1\n", &qubic), None);
+    }
 
-        game.board = [['X', 'O', 'O'],
-                      ['O', 'X', ' '],
-                      ['X', 'X', ' ']];
-        assert_ne!(game.is_draw(), true);
+    #[test]
+    fn test_ultimate() {
+        // Tests the ultimate flag, its board,square input and its grid of playable squares
+        let args = |line: &str| -> Vec<String> { line.split_whitespace().map(String::from).collect() };
+        assert_eq!(parse_options(&args("--ultimate --seed 3")).map(|options| (options.variant, options.seed)),
+                   Ok((Variant::Ultimate, Some(3))));
+        let mut game = Ultimate::new();
+        assert_eq!(parse_ultimate_location("5,9\n", &game), Some(44));
+        assert_eq!(parse_ultimate_location("80\n", &game), Some(80));
+        assert_eq!(parse_ultimate_location("81\n", &game), None);
+        assert_eq!(parse_ultimate_location("10,1\n", &game), None);
+        let lines: Vec<String> = ultimate_board(&game).lines().map(String::from).collect();
+        assert_eq!(lines[0], "   0 |  1 |  2  ||   9 | 10 | 11  ||  18 | 19 | 20");
+        assert_eq!(lines[1], " ---------------||----------------||---------------");
+        assert_eq!(lines[5], format!(" {}", "=".repeat(50)));
+        game.place(40).unwrap();
+        let lines: Vec<String> = ultimate_board(&game).lines().map(String::from).collect();
+        assert_eq!(lines[8], "     |    |     ||  39 |  X | 41  ||     |    |   ");
+    }
 
-        game.board = [[' ', ' ', ' '],
-                      [' ', ' ', ' '],
-                      [' ', ' ', ' ']];
-        assert_ne!(game.is_draw(), true);
+    #[test]
+    fn test_notakto() {
+        // Tests the notakto flags, its board,square input, its side-by-side boards and its hint
+        let args = |line: &str| -> Vec<String> { line.split_whitespace().map(String::from).collect() };
+        assert_eq!(parse_options(&args("--notakto --boards 2")).map(|options| (options.variant, options.boards)),
+                   Ok((Variant::Notakto, 2)));
+        let mut game = Notakto::new(2);
+        assert_eq!(parse_notakto_location("2,5\n", &game), Some(13));
+        assert_eq!(parse_notakto_location("17\n", &game), Some(17));
+        assert_eq!(parse_notakto_location("18\n", &game), None);
+        assert_eq!(parse_notakto_location("3,1\n", &game), None);
+        assert_eq!(notakto_hint(&game), "Value c² :: every move loses against perfect play");
+        for &loc in &[0, 1, 2] {
+            game.place(loc).unwrap();
+        }
+        let lines: Vec<String> = notakto_board(&game).lines().map(String::from).collect();
+        assert_eq!(lines[0], "   X |  X |  X  ||   9 | 10 | 11");
+        assert_eq!(lines[1], " ---------------||---------------");
+        assert_eq!(lines[2], "     |    |     ||  12 | 13 | 14");
+        assert_eq!(notakto_hint(&game), "Value c :: winning moves 13");
+    }
 
-        game.board = [['O', 'X', 'X'],
-                      ['O', 'X', 'O'],
-                      [' ', ' ', 'X']];
-        assert_ne!(game.is_draw(), true);
+    #[test]
+    fn test_parse_command() {
+        // Tests that undo and redo are recognised alongside locations
+        let game = Game::new();
+        assert_eq!(parse_command("u\n", &game), Some(Command::Undo));
+        assert_eq!(parse_command("redo\n", &game), Some(Command::Redo));
+        assert_eq!(parse_command("h\n", &game), Some(Command::Hint));
+        assert_eq!(parse_command("4\n", &game), Some(Command::Place(4)));
+        assert_eq!(parse_command("x\n", &game), None);
     }
 
     #[test]
-    fn test_switch_player() {
-        // Tests that players are switched correctly
+    fn test_declare_winner_p1() {
+        // Test that the correct winner was declared
+        // by testing the printed variable's value (left-hand assert_eq param)
+        // with the expected value (right-hand assert_eq param)
         let mut game = Game::new();
-        game.curr_player = 0;
-        assert_eq!(game.switch_player(), 1);
-
-        game.curr_player = 1;
-        assert_eq!(game.switch_player(), 0);
+        for &loc in &[0, 3, 1, 4, 2] {
+            game.place(loc).unwrap();
+        }
+        declare_winner(&game);
+        assert_eq!(game.players[game.winner().unwrap()], P1);
     }
 
     #[test]
-    fn test_end_game_true() {
-        // Tests that is_endgame returns True if a win/drawn state is reached
+    fn test_declare_winner_p2() {
+        // Test that the correct winner was declared
+        // by testing the printed variable's value (left-hand assert_eq param)
+        // with the expected value (right-hand assert_eq param)
         let mut game = Game::new();
-
-        // drawn end game
-        game.board = [['X', 'O', 'O'],
-                      ['O', 'X', 'X'],
-                      ['X', 'O', 'X']];
-        assert_eq!(game.is_endgame(), true);
-
-        // won end game
-        game.board = [['X', 'O', 'O'],
-                      ['O', 'X', 'O'],
-                      ['X', 'X', 'X']];
-        assert_eq!(game.is_endgame(), true);
+        for &loc in &[0, 3, 1, 4, 8, 5] {
+            game.place(loc).unwrap();
+        }
+        declare_winner(&game);
+        assert_eq!(game.players[game.winner().unwrap()], P2);
     }
 
     #[test]
-    fn test_end_game_false() {
-        // Tests that is_endgame returns False if the board is incomplete with no win
+    fn test_declare_winner_misere() {
+        // Test that under misère rules the player who completed a line is declared the loser
         let mut game = Game::new();
-
-        game.board = [['O', ' ', 'O'],
-                      ['O', 'X', 'X'],
-                      ['X', 'O', 'X']];
-        assert_eq!(game.is_endgame(), false);
-
-        game.board = [['O', 'X', 'X'],
-                      [' ', ' ', 'X'],
-                      [' ', ' ', 'O']];
-        assert_eq!(game.is_endgame(), false);
-
-        game.board = [['X', 'O', 'O'],
-                      ['X', 'O', 'X'],
-                      [' ', 'X', ' ']];
-        assert_eq!(game.is_endgame(), false);
+        game.set_rules(Rules::Misere);
+        for &loc in &[0, 3, 1, 4, 2] {
+            game.place(loc).unwrap();
+        }
+        declare_winner(&game);
+        assert_eq!(game.players[game.winner().unwrap()], P2);
     }
 }
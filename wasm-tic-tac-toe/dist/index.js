@@ -3,12 +3,18 @@
 *  https://rustwasm.github.io/docs/book/game-of-life/hello-world.html
 */
 
-import { Game } from "../pkg/wasm_tic_tac_toe";
+import { Difficulty, Game, OutcomeStatus, Rules, Strategy, Value } from "../pkg/wasm_tic_tac_toe";
 
-const DRAW = 9;
 const MS = 1000;
 const AUTO_DELAY = 0.5 * MS;
 const MANUAL_DELAY = 1.0 * MS;
+const DIFFICULTIES = {
+  random: Difficulty.Random,
+  easy: Difficulty.Easy,
+  medium: Difficulty.Medium,
+  hard: Difficulty.Hard,
+  perfect: Difficulty.Perfect
+};
 
 document.getElementById("title").textContent = "Rusty Tic Tac Toe\nMagically Compiled to WASM";
 listen();
@@ -28,13 +34,21 @@ function listen() {
     reset: false
   }
 
-  // listen for user selection of automatic/manual play
+  // listen for user selection of automatic/manual play,
+  // replaying the random moves of an earlier game when the page is opened with `?seed=N`
   let game = Game.new();
+  let seed = new URLSearchParams(window.location.search).get("seed");
+  if (seed !== null) {
+    game.set_seed(BigInt(seed));
+  }
+  console.log('seed = ' + game.get_seed());
 
   // setup game play style by waiting for the user
-  // to select automatic or manual play
+  // to select automatic or manual play and the automatic players' difficulty
   let auto_play = document.getElementById("auto");
   let manual_play = document.getElementById("manual");
+  let difficulty = document.getElementById("difficulty");
+  let misere = document.getElementById("misere");
 
   // settings for automatic play button
   auto_play.onclick = (e) => {
@@ -42,7 +56,8 @@ function listen() {
     settings.player1_type = true;
     settings.player2_type = true;
     settings.manual = false;
-    game.start(settings.player1_type, settings.player2_type)
+    let level = DIFFICULTIES[difficulty.value];
+    game.start(level, level, Strategy.AlphaBeta, misere.checked ? Rules.Misere : Rules.Standard)
     begin(game, settings)
   };
 
@@ -51,7 +66,7 @@ function listen() {
     settings.player1_type = false;
     settings.player2_type = true;
     settings.manual = true;
-    game.start(settings.player1_type, settings.player2_type)
+    game.start(undefined, DIFFICULTIES[difficulty.value], Strategy.AlphaBeta, misere.checked ? Rules.Misere : Rules.Standard)
     begin(game, settings)
   };
 }
@@ -116,14 +131,13 @@ async function manual_play(game, manual_dialogue, settings) {
   settings.reset_btn.onclick = function() {
       local_reset = reset_all(game, manual_dialogue, settings);
   };
-  document.getElementById("manual-dialogue").textContent = "Where do you want to place your piece?";
+  document.getElementById("manual-dialogue").textContent = "Where do you want to place your piece? (u: undo, r: redo, h: hint)";
 
   // if reset button was not selected, continue game play
   while (!local_reset && !end_game) {
     await sleep(settings.manual);
 
     // listen for a valid keystroke by the user (or an `escape` equivalent)
-    // 9 represents an invalid input value
     let success = manual_tick(game);
     end_game = game.get_end_game();
 
@@ -137,8 +151,9 @@ async function manual_play(game, manual_dialogue, settings) {
       end_game = game.get_end_game();
     }
 
-    // TODO: update rendering of indexed board (in Rust) so that successful cell placement removes numeral
+    // re-render both boards so that placed pieces remove their numeral from the overlay
     settings.board.textContent = game.render_board();
+    render_overlay(game, settings.trans_board);
   }
 
   // select next function based on whether the game
@@ -152,7 +167,7 @@ async function manual_play(game, manual_dialogue, settings) {
 
 function manual_tick(game) {
   // Run the game for one "tick" or move 
-  let success = 9;
+  let success = null;
   let key = -2;
   
   // following code source: https://medium.com/@uistephen/keyboardevent-key-for-cross-browser-key-press-check-61dbad0a067a
@@ -165,8 +180,28 @@ function manual_tick(game) {
     key = e.key || e.keyCode;
 
     if (key >= "0" && key <= "8") {
+      // ignore keys for occupied cells, the engine would reject them anyway
+      if (!game.legal_moves().includes(Number(key))) {
+        console.log('occupied cell, key = ' + key)
+        return success;
+      }
       console.log('valid key = ' + key)
-      return game.update(key);
+      try {
+        return game.update(key);
+      } catch (err) {
+        // the engine rejected the move, e.g. the square is already taken
+        console.log('rejected move: ' + err.message)
+        return success;
+      }
+    } else if (key == "u" || key == "r") {
+      // take back (or replay) the last turn, including the automatic player's reply
+      let moves = (key == "u") ? game.undo() : game.redo();
+      console.log((key == "u" ? 'undid ' : 'redid ') + moves + ' move(s)')
+      return success;
+    } else if (key == "h") {
+      // coach the player with how every open square fares under perfect play
+      document.getElementById("manual-dialogue").textContent = describe_hints(game.get_evaluations());
+      return success;
     } else if (key == "Escape" || key == "esc" || key == "27") {
       console.log('escape key pressed, key = ' + key)
       return -1;
@@ -175,6 +210,24 @@ function manual_tick(game) {
   });
 }
 
+function describe_hints(evaluations) {
+  // summarizes the engine's evaluations by result, e.g. "win in 1: 2 | draw: 5 | loss in 2: 6, 7, 8"
+  let groups = new Map();
+  for (const evaluation of evaluations) {
+    let label = "unknown";
+    if (evaluation.value() === Value.Win) {
+      label = "win in " + evaluation.plies();
+    } else if (evaluation.value() === Value.Draw) {
+      label = "draw";
+    } else if (evaluation.value() === Value.Loss) {
+      label = "loss in " + evaluation.plies();
+    }
+    groups.set(label, (groups.get(label) || []).concat([evaluation.loc()]));
+  }
+  let hints = Array.from(groups, ([label, locs]) => label + ": " + locs.join(", "));
+  return "Hint :: " + hints.join(" | ");
+}
+
 async function auto_play(game, manual_dialogue, settings) {
   // start the game and loop until end game is reached
 
@@ -209,19 +262,19 @@ async function auto_play(game, manual_dialogue, settings) {
 
 function auto_tick(game, board) {
   // Run the game for one "tick" or move 
-  let success = game.update();
-  if (success > -1 || success < 9) {
+  try {
+    game.update();
     board.textContent = game.render_board();
-  } else {
-    throw "auto_tick function failed to update board";
+  } catch (err) {
+    throw "auto_tick function failed to update board: " + err.message;
   }
 }
 
 function game_over_msg(game, winner) {
   // displays whether the game ended in a draw or a win,
   // as well as the winner in the latter case
-  const game_over = game.get_winner();
-  if (game_over === DRAW) {
+  const outcome = game.get_outcome();
+  if (outcome.status() === OutcomeStatus.Draw) {
     winner.textContent = game.declare_draw();  
   } else {
     winner.textContent = game.declare_winner();  
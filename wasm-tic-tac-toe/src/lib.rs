@@ -6,76 +6,243 @@
 mod utils;
 
 use wasm_bindgen::prelude::*;
-use rand::{thread_rng, Rng};
+use tic_tac_toe_core as engine;
+use std::time::Duration;
+use tic_tac_toe_core::hint;
+use tic_tac_toe_core::mcts::MctsConfig;
+use tic_tac_toe_core::notakto::{self, Notakto};
+use tic_tac_toe_core::player::{AiPlayer, Human, Player, RandomPlayer};
+use tic_tac_toe_core::solve;
+use tic_tac_toe_core::ultimate::{Ultimate, BOARDS, SIDE, SQUARES};
+use tic_tac_toe_core::{index_width, Outcome, P1, P2, SIZE};
 
 // When the `wee_alloc` feature is enabled, use `wee_alloc` as the global allocator.
 #[cfg(feature = "wee_alloc")]
 #[global_allocator]
 static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
 
-const P1: char = 'X';           // player 1's piece
-const P2: char = 'O';           // player 2's piece
-const SIZE: usize = 3;          // row/col sizes for board
-const NO_WIN: usize = 9;        // default, invalid value to represent no winner
-const BOARD_IDX: [[char; 3]; 3] = [['0', '1', '2'],     // indexed board for user's reference
-                                   ['3', '4', '5'], 
-                                   ['6', '7', '8']];
 
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    // Javascript-friendly mirror of the engine's `Strategy` for automatic players
+    Random,
+    Minimax,
+    AlphaBeta,
+    Mcts,
+}
+
+impl From<Strategy> for engine::Strategy {
+    fn from(strategy: Strategy) -> engine::Strategy {
+        // Map the exported strategy onto the engine's
+        match strategy {
+            Strategy::Random => engine::Strategy::Random,
+            Strategy::Minimax => engine::Strategy::Minimax,
+            Strategy::AlphaBeta => engine::Strategy::AlphaBeta,
+            Strategy::Mcts => engine::Strategy::Mcts,
+        }
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Difficulty {
+    // Javascript-friendly mirror of the engine's `Difficulty` for automatic players
+    Random,
+    Easy,
+    Medium,
+    Hard,
+    Perfect,
+}
+
+impl From<Difficulty> for engine::Difficulty {
+    fn from(difficulty: Difficulty) -> engine::Difficulty {
+        // Map the exported difficulty onto the engine's
+        match difficulty {
+            Difficulty::Random => engine::Difficulty::Random,
+            Difficulty::Easy => engine::Difficulty::Easy,
+            Difficulty::Medium => engine::Difficulty::Medium,
+            Difficulty::Hard => engine::Difficulty::Hard,
+            Difficulty::Perfect => engine::Difficulty::Perfect,
+        }
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rules {
+    // Javascript-friendly mirror of the engine's `Rules`: whether completing a line wins or loses
+    Standard,
+    Misere,
+}
+
+impl From<Rules> for engine::Rules {
+    fn from(rules: Rules) -> engine::Rules {
+        // Map the exported rules onto the engine's
+        match rules {
+            Rules::Standard => engine::Rules::Standard,
+            Rules::Misere => engine::Rules::Misere,
+        }
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Restriction {
+    // Javascript-friendly mirror of the engine's `Restriction`: whether overlines count and renju fouls apply
+    Freestyle,
+    Exact,
+    Renju,
+}
+
+impl From<Restriction> for engine::Restriction {
+    fn from(restriction: Restriction) -> engine::Restriction {
+        // Map the exported restriction onto the engine's
+        match restriction {
+            Restriction::Freestyle => engine::Restriction::Freestyle,
+            Restriction::Exact => engine::Restriction::Exact,
+            Restriction::Renju => engine::Restriction::Renju,
+        }
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutcomeStatus {
+    // Javascript-friendly tag for the engine's `Outcome`
+    InProgress,
+    Win,
+    Draw,
+}
 
-#[derive(Debug, PartialEq)]
-struct AutoPlay {
-    // Struct detailing whether players move automatically or manually
-    play_type: [bool; 2],           // True: automatic random moves, False: manual moves
-    play_type_str: [String; 2],     // "automatic" or "manual"
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameOutcome {
+    // State of play passed to javascript, mirroring the engine's `Outcome`
+    status: OutcomeStatus,      // in progress, won or drawn
+    player: Option<usize>,      // winning player (0 or 1) when the game is won
+    line: Vec<usize>,           // locations (indices) of the winning line when the game is won
 }
 
-impl Default for AutoPlay {
-    fn default() -> AutoPlay {
-        // By default, both players are automated to play random, legal moves
-        AutoPlay{ play_type: [true, true], 
-                  play_type_str: ["automatic".to_string(), "automatic".to_string()]}
+#[wasm_bindgen]
+impl GameOutcome {
+    pub fn status(&self) -> OutcomeStatus {
+        // Returns whether the game is in progress, won or drawn
+        self.status
+    }
+
+    pub fn player(&self) -> Option<usize> {
+        // Returns the winning player, or `undefined` if nobody has won
+        self.player
+    }
+
+    pub fn line(&self) -> Vec<usize> {
+        // Returns the winning line's locations, or an empty array if nobody has won
+        self.line.clone()
     }
 }
 
-#[derive(Debug, PartialEq)]
-struct Coord {
-    // Struct for mapping array indices to coordinates
-    x: usize,       // x-coordinate
-    y: usize,       // y-coordinate
-    legal: bool,    // flag: True if it is legal to place a piece on the coordinate, False if coordinate is already full
+impl From<&Outcome> for GameOutcome {
+    fn from(outcome: &Outcome) -> GameOutcome {
+        // Flatten the engine's outcome into fields wasm-bindgen can export
+        match outcome {
+            Outcome::InProgress => GameOutcome { status: OutcomeStatus::InProgress, player: None, line: vec![] },
+            Outcome::Win { player, line } => GameOutcome { status: OutcomeStatus::Win,
+                                                           player: Some(*player),
+                                                           line: line.clone() },
+            Outcome::Draw => GameOutcome { status: OutcomeStatus::Draw, player: None, line: vec![] },
+        }
+    }
 }
 
-#[derive(Debug, PartialEq)]
-struct WinState {
-    // Struct containing representations of win states
-    p1_win_state: Vec<char>,    // represents player 1's win state
-    p2_win_state: Vec<char>,    // represents player 2's win state
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Value {
+    // Javascript-friendly mirror of the engine's `Value` of a move under perfect play
+    Loss,
+    Draw,
+    Win,
 }
 
-impl Default for WinState {
-    fn default() -> WinState {
-        // Generates winning state sized to default `SIZE`
-        let mut win_state = WinState { p1_win_state: vec![], p2_win_state: vec![] };
-        for _i in 0..SIZE {
-            win_state.p1_win_state.push(P1);
-            win_state.p2_win_state.push(P2);
+impl From<solve::Value> for Value {
+    fn from(value: solve::Value) -> Value {
+        // Map the engine's value onto the exported one
+        match value {
+            solve::Value::Loss => Value::Loss,
+            solve::Value::Draw => Value::Draw,
+            solve::Value::Win => Value::Win,
         }
-        win_state
     }
 }
 
 #[wasm_bindgen]
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Evaluation {
+    // How one open square fares for the player to move, passed to javascript to coach players
+    loc: usize,                 // location (index) of the square
+    value: Option<Value>,       // result of playing there, None if the search could not settle it
+    plies: Option<usize>,       // moves until that result, counting this one
+}
+
+#[wasm_bindgen]
+impl Evaluation {
+    pub fn loc(&self) -> usize {
+        // Returns the location (index) of the square
+        self.loc
+    }
+
+    pub fn value(&self) -> Option<Value> {
+        // Returns the result of playing there, or `undefined` if it is unknown
+        self.value
+    }
+
+    pub fn plies(&self) -> Option<usize> {
+        // Returns the number of moves until that result, or `undefined` if it is unknown
+        self.plies
+    }
+}
+
+impl From<hint::Evaluation> for Evaluation {
+    fn from(evaluation: hint::Evaluation) -> Evaluation {
+        // Copy the engine's evaluation into fields wasm-bindgen can export
+        Evaluation { loc: evaluation.loc, value: evaluation.value.map(Value::from), plies: evaluation.plies }
+    }
+}
+
+#[wasm_bindgen]
 pub struct Game {
-    // Struct with tic-tac-toe game settings and components
-    board: [[char; SIZE]; SIZE],    // tic tac toe board
-    curr_player: usize,             // current player 
-    players: [char; 2],             // players represented by pieces
-    auto_play: AutoPlay,            // type of play for each player
-    end_game: bool,                 // game status: False if in play, True if ended by win/draw
-    coordinates: Vec<Coord>,        // coordinates for moves
-    win_states: WinState,           // win states for players
-    winner: usize,                  // specifies winner if there is one
+    // Browser-facing wrapper around the shared rules engine
+    engine: engine::Game,           // board, players and endgame state
+    players: [Box<dyn Player>; 2],  // who chooses the moves for each side
+    mcts_playouts: Option<u64>,     // playouts per move for `Strategy.Mcts` in place of the level's, None to follow the level
+    mcts_time: Option<Duration>,    // time limit per move for `Strategy.Mcts`, None for no limit
+    mcts_exploration: Option<f64>,  // UCT exploration constant for `Strategy.Mcts`, None for the default
+}
+
+impl Game {
+    fn from_engine(engine: engine::Game) -> Game {
+        // Wraps an engine game with random players on both sides until `start` chooses them
+        Game { engine, players: [Box::new(RandomPlayer), Box::new(RandomPlayer)],
+               mcts_playouts: None, mcts_time: None, mcts_exploration: None }
+    }
+
+    fn ai_player(&self, strategy: engine::Strategy, difficulty: engine::Difficulty) -> AiPlayer {
+        // Builds an automatic player, with the MCTS settings tuning a Monte Carlo tree search
+        match strategy {
+            engine::Strategy::Mcts => {
+                let config = MctsConfig::for_difficulty(difficulty)
+                    .tuned(self.mcts_playouts, self.mcts_time, self.mcts_exploration);
+                AiPlayer::with_mcts(difficulty, config)
+            },
+            strategy => AiPlayer::new(strategy, difficulty),
+        }
+    }
+}
+
+impl Default for Game {
+    fn default() -> Game {
+        Game::new()
+    }
 }
 
 #[wasm_bindgen]
@@ -84,115 +251,273 @@ impl Game {
         // Allows for console.log debugging of Rust
         utils::set_panic_hook();
 
-        // Initializes the game board, first player piece and default autoplay for both players
-        Self {
-            board: [[' ', ' ', ' '], 
-                    [' ', ' ', ' '], 
-                    [' ', ' ', ' ']],
-            curr_player: 0,
-            players: [P1, P2],
-            auto_play: AutoPlay::default(), 
-            end_game: false,
-            coordinates: coord_mapping(),
-            win_states: WinState::default(),
-            winner: NO_WIN,
-        }
+        // Initializes the game board, first player piece and random players on both sides
+        Self::from_engine(engine::Game::new())
     }
 
-    pub fn start(&mut self, p1_auto: bool, p2_auto: bool) {
-        // Set the automatic/manual play settings for each player in order to start the game
-        // Create the coordinates per game size
-        self.end_game = false;
-        self.auto_play.play_type = [p1_auto, p2_auto];
-
-        match self.auto_play {
-            AutoPlay { play_type: [false, false], .. } => {
-                self.auto_play.play_type_str = ["manual".to_string(), "manual".to_string()];
-            },
-            AutoPlay { play_type: [true, false], .. } => {
-                self.auto_play.play_type_str = ["automatic".to_string(), "manual".to_string()];
-            },
-            AutoPlay { play_type: [false, true], .. } => {
-                self.auto_play.play_type_str = ["manual".to_string(), "automatic".to_string()];
-            },
-            AutoPlay { .. } => {
-                self.auto_play.play_type_str = ["automatic".to_string(), "automatic".to_string()];
-            },
+    pub fn with_size(width: usize, height: usize, win_length: usize) -> Result<Game, JsError> {
+        // Initializes a `width` x `height` game won by `win_length` pieces in a row,
+        // throwing a javascript `Error` if the engine rejects the board
+        Self::with_layers(width, height, 1, win_length)
+    }
+
+    pub fn with_layers(width: usize, height: usize, layers: usize, win_length: usize) -> Result<Game, JsError> {
+        // Initializes a game of `layers` stacked `width` x `height` boards won by `win_length` pieces
+        // in a row within a layer or through the layers, e.g. 4x4x4 Qubic,
+        // throwing a javascript `Error` if the engine rejects the board
+        utils::set_panic_hook();
+        Ok(Self::from_engine(engine::Game::try_with_layers(width, height, layers, win_length)?))
+    }
+
+    pub fn get_width(&self) -> usize {
+        // Returns the number of columns on the board
+        self.engine.width
+    }
+
+    pub fn get_height(&self) -> usize {
+        // Returns the number of rows in each layer of the board
+        self.engine.height
+    }
+
+    pub fn get_layers(&self) -> usize {
+        // Returns the number of layers stacked into the board, 1 for a flat board
+        self.engine.layers
+    }
+
+    pub fn get_seed(&self) -> u64 {
+        // Returns the seed behind every random move, so a game can be replayed
+        self.engine.seed()
+    }
+
+    pub fn set_seed(&mut self, seed: u64) {
+        // Restarts random play from `seed` (a javascript `BigInt`)
+        self.engine.set_seed(seed);
+    }
+
+    pub fn start(&mut self, p1: Option<Difficulty>, p2: Option<Difficulty>, strategy: Strategy, rules: Rules)
+                 -> Result<(), JsError> {
+        // Set the automatic/manual play settings for each player in order to start the game:
+        // `undefined` for manual play, otherwise the difficulty of an automatic player choosing moves by `strategy`,
+        // and whether completing a line wins or loses. Throws a javascript `Error` if an automatic player
+        // cannot search the board at its difficulty, or is not `Difficulty.Random` in a wild game
+        self.engine.set_rules(rules.into());
+        let player = |difficulty: Option<Difficulty>| -> Result<Box<dyn Player>, JsError> {
+            match difficulty {
+                Some(difficulty) => {
+                    let ai = self.ai_player(strategy.into(), difficulty.into());
+                    match ai.supports(&self.engine) {
+                        true => Ok(Box::new(ai)),
+                        false => Err(JsError::new(&format!("{} play cannot search this board", ai.name()))),
+                    }
+                },
+                None => Ok(Box::new(Human)),
+            }
+        };
+        self.players = [player(p1)?, player(p2)?];
+        Ok(())
+    }
+
+    pub fn set_mcts_playouts(&mut self, playouts: Option<u32>) {
+        // Sets the playouts per move of `Strategy.Mcts` players started after this call,
+        // or with `undefined` goes back to the difficulty's playouts
+        self.mcts_playouts = playouts.map(u64::from);
+    }
+
+    pub fn set_mcts_time(&mut self, milliseconds: Option<u32>) {
+        // Sets a time limit per move, in milliseconds, for `Strategy.Mcts` players started after this call,
+        // or with `undefined` lifts it; without `set_mcts_playouts` the search runs for the whole time
+        self.mcts_time = milliseconds.map(|milliseconds| Duration::from_millis(milliseconds.into()));
+    }
+
+    pub fn set_mcts_exploration(&mut self, exploration: Option<f64>) -> Result<(), JsError> {
+        // Sets the UCT exploration constant of `Strategy.Mcts` players started after this call, or with `undefined`
+        // goes back to the default; throws a javascript `Error` if it is negative or not a number
+        if exploration.is_some_and(|exploration| !exploration.is_finite() || exploration < 0.0) {
+            return Err(JsError::new("the exploration constant must be a number that is not negative"));
         }
+        self.mcts_exploration = exploration;
+        Ok(())
+    }
+
+    pub fn set_restriction(&mut self, restriction: Restriction) {
+        // Choose whether overlines count and whether renju fouls are forbidden for player 1,
+        // e.g. `Restriction.Renju` on a 15x15 board won by five in a row
+        self.engine.set_restriction(restriction.into());
+    }
+
+    pub fn set_gravity(&mut self, gravity: bool) {
+        // Choose whether pieces drop to the lowest open square of their column, as in Connect Four
+        // on a 7x6 board won by four in a row
+        self.engine.set_gravity(gravity);
+    }
+
+    pub fn set_wild(&mut self, wild: bool) {
+        // Choose whether each player may place either piece, with a line won by whoever completes it;
+        // call before `start`, and pass the chosen piece to `update_with_piece`
+        self.engine.set_wild(wild);
+    }
+
+    pub fn get_columns(&self) -> usize {
+        // Returns the number of columns pieces can be dropped into, across every layer
+        self.engine.columns()
+    }
+
+    pub fn drop_location(&self, column: usize) -> Option<usize> {
+        // Returns the location (index) a piece dropped into the column (counted from 0) would land on,
+        // or `undefined` if the column is full or not on the board; pass it to `update` to play it
+        self.engine.drop_location(column)
+    }
+
+    pub fn parse_square(&self, square: &str) -> Option<usize> {
+        // Returns the location (index) of a square named by column letter and row number counted
+        // from the bottom, e.g. "h8", or `undefined` if it is not on the board
+        self.engine.parse_notation(square)
+    }
+
+    pub fn square_name(&self, loc: usize) -> Option<String> {
+        // Names the location (index) by column letter and row number, e.g. "h8",
+        // or returns `undefined` on layered boards and boards wider than the alphabet
+        self.engine.notation(loc)
     }
 
     pub fn render_players(&self) -> String {
-        // Render players and their playing type (e.g. automatic/manual)
-        let status: String = format!("Player 1 :: {} ({} play)\nPlayer 2 :: {} ({} play)", 
-                                     P1, &self.auto_play.play_type_str[0], 
-                                     P2, &self.auto_play.play_type_str[1]);
+        // Render players and their playing type (e.g. hard/manual)
+        let status: String = format!("Player 1 :: {} ({} play)\nPlayer 2 :: {} ({} play)",
+                                     P1, self.players[0].name(),
+                                     P2, self.players[1].name());
         status
     }
 
 
     pub fn render_board(&self) -> String {
         // Render board as a string (for WASM)
+        // with each layer of a layered board under its own heading
         let mut board_state: String = "".to_string();
-        let mut total_lines = &SIZE - 1;
-        for row in &self.board {
-            board_state += &format!("\n {} ║ {} ║ {}\n", row[0], row[1], row[2]);
-            if total_lines > 0 {
-                board_state += &format!("═══╬═══╬═══\n");
-                total_lines -= 1;
+        let divider = vec!["═══"; self.engine.width].join("╬");
+        for (layer, rows) in self.engine.board.chunks(self.engine.height).enumerate() {
+            if self.engine.layers > 1 {
+                board_state += &format!("\n Layer {}\n", layer + 1);
+            }
+            let mut total_lines = self.engine.height - 1;
+            for row in rows {
+                let squares: Vec<String> = row.iter().map(|square| square.to_string()).collect();
+                board_state += &format!("\n {}\n", squares.join(" ║ "));
+                if total_lines > 0 {
+                    board_state += &format!("{}\n", divider);
+                    total_lines -= 1;
+                }
             }
         }
-        board_state += &format!("\n");
+        board_state += "\n";
         board_state
     }
 
+    pub fn render_labelled_board(&self) -> String {
+        // Render board as a string (for WASM) with column letters above and below and row numbers,
+        // counted from the bottom, either side, for boards too large for index labels such as 15x15 gomoku
+        let height = self.engine.height;
+        let row_width = height.to_string().len();
+        let letters: Vec<String> = (0..self.engine.width).map(|col| ((b'a' + col as u8) as char).to_string()).collect();
+        let header = format!("\n {:>width$}  {}\n", "", letters.join("   "), width = row_width);
+        let mut board_state = header.clone();
+        for (row, squares) in self.engine.board.iter().enumerate() {
+            let squares: Vec<String> = squares.iter().map(|square| square.to_string()).collect();
+            board_state += &format!("\n {:>width$}  {}  {}\n", height - row, squares.join(" ║ "), height - row,
+                                    width = row_width);
+        }
+        board_state + &header
+    }
+
     pub fn render_indexed_board(&mut self) -> String {
-        // Render indexed board as a string (for WASM) to allow for keystrokes
+        // Render indexed board as a string (for WASM) to allow for keystrokes,
+        // leaving occupied squares blank so only playable indices are shown, and layer headings
+        // blank so the overlay lines up with `render_board`
         let mut board_state: String = "".to_string();
-        let mut total_lines = &SIZE - 1;
-        for row in &BOARD_IDX {
-            board_state += &format!("\n {}   {}   {}\n", row[0], row[1], row[2]);
-            if total_lines > 0 {
-                board_state += &format!("           \n");
-                total_lines -= 1;
+        let width = self.engine.width;
+        let cell_width = index_width(self.engine.total_squares());
+        let divider = " ".repeat((cell_width + 3) * width - 1);
+        for row in 0..self.engine.height * self.engine.layers {
+            if self.engine.layers > 1 && row % self.engine.height == 0 {
+                board_state += "\n\n";
+            }
+            let labels: Vec<String> = (0..width)
+                .map(|col| row * width + col)
+                .map(|loc| match self.engine.coordinates[loc].legal {
+                    true => format!("{:>cell$}", loc, cell = cell_width),
+                    false => " ".repeat(cell_width),
+                })
+                .collect();
+            board_state += &format!("\n {}\n", labels.join("   "));
+            if (row + 1) % self.engine.height > 0 {
+                board_state += &format!("{}\n", divider);
             }
         }
-        board_state += &format!("\n");
+        board_state += "\n";
         board_state
     }
 
-    pub fn update(&mut self, manual_move: usize) -> usize { 
-        // Have the current player choose a location for their move 
-        let selected_move: usize = manual_move;
-        let loc: usize = match &self.auto_play.play_type[self.curr_player] {
-            true => self.auto_move(),
-            false => self.manual_move(selected_move),
+    pub fn update(&mut self, manual_move: usize) -> Result<usize, JsError> {
+        // Have the current player choose a location for their move, placing a manual player's own piece,
+        // throwing a javascript `Error` if the engine rejects a manual move
+        self.update_with_piece(manual_move, self.engine.curr_player)
+    }
+
+    pub fn update_with_piece(&mut self, manual_move: usize, manual_piece: usize) -> Result<usize, JsError> {
+        // Have the current player choose a location and piece (0 for X, 1 for O) for their move,
+        // which in wild games may be either piece, throwing a javascript `Error` if the engine rejects a manual move
+        let (loc, piece) = match self.players[self.engine.curr_player].choose_move(&self.engine) {
+            Some(loc) => (loc, self.engine.random_piece(loc)),
+            None => (manual_move, manual_piece),
         };
 
-        if loc == 9 {
-            return loc;
-        }
+        // Update the board, check for endgame and change players
+        self.engine.place_symbol(loc, piece)?;
+        Ok(loc)
+    }
+
+    pub fn undo(&mut self) -> usize {
+        // Takes back moves until it is the current player's turn again, so an automatic
+        // opponent's reply is undone along with the manual player's move; returns the number undone
+        self.engine.undo_turn()
+    }
+
+    pub fn redo(&mut self) -> usize {
+        // Replays undone moves until it is the current player's turn again; returns the number replayed
+        self.engine.redo_turn()
+    }
 
-        // Update the board and coordinates
-        let x = self.coordinates[loc].x;
-        let y = self.coordinates[loc].y;
-        self.board[x][y] = self.players[self.curr_player];
-        self.coordinates[loc].legal = false;
+    pub fn get_history(&self) -> Vec<usize> {
+        // Returns the locations (indices) played so far, in order
+        self.engine.history.iter().map(|played| played.loc).collect()
+    }
 
-        // Check for endgame and change players
-        self.end_game = self.is_endgame();
-        self.curr_player = self.switch_player();
+    pub fn get_evaluations(&self) -> Result<Vec<Evaluation>, JsError> {
+        // Returns how every open square fares for the current player, in location (index) order,
+        // so the page can shade the indexed board as a hint, throwing a javascript `Error` in wild games
+        match hint::evaluate(&self.engine) {
+            Some(evaluations) => Ok(evaluations.into_iter().map(Evaluation::from).collect()),
+            None => Err(JsError::new("hints are not available in wild games")),
+        }
+    }
 
-        loc
+    pub fn legal_moves(&self) -> Vec<usize> {
+        // Returns the locations (indices) that can still be played, so occupied cells can be disabled
+        self.engine.legal_moves()
+    }
+
+    pub fn is_legal(&self, loc: usize) -> bool {
+        // Returns True if the location (index) can be played by the current player
+        self.engine.is_legal(loc)
     }
 
     pub fn get_end_game(&self) -> bool {
         // Returns end_game boolean
-        self.end_game
+        self.engine.end_game
     }
 
-    pub fn get_winner(&self) -> usize {
-        // Returns winner value
-        self.winner
+    pub fn get_outcome(&self) -> GameOutcome {
+        // Returns whether the game is in progress, won (with the winner and line) or drawn
+        GameOutcome::from(&self.engine.outcome)
     }
 
     pub fn declare_draw(&self) -> String {
@@ -202,127 +527,326 @@ impl Game {
 
     pub fn declare_winner(&self) -> String {
         // Winner message
-        let winner = format!("Player {} is the WINNER!", self.players[self.winner]);
-        winner
+        match (self.engine.winner(), self.engine.rules) {
+            (Some(player), engine::Rules::Misere) => format!("Player {} is the WINNER! Player {} completed a line.",
+                                                             self.engine.players[player], self.engine.players[1 - player]),
+            (Some(player), _) => format!("Player {} is the WINNER!", self.engine.players[player]),
+            (None, _) => self.declare_draw(),
+        }
     }
 
     pub fn reset(&mut self) {
         // Reset Game
-        self.board = [[' ', ' ', ' '], 
-                     [' ', ' ', ' '], 
-                     [' ', ' ', ' ']];
-        self.curr_player = 0;
-        self.end_game = true;
-        self.coordinates.clear();
-        self.coordinates = coord_mapping();
-        self.winner = NO_WIN;
+        self.engine.reset();
     }
 }
 
-impl Game {
+#[wasm_bindgen]
+pub struct UltimateGame {
+    // Browser-facing wrapper around the engine's ultimate tic-tac-toe, a 3x3 meta-board of 3x3 games
+    engine: Ultimate,   // small boards, meta-board and endgame state
+    auto: [bool; 2],    // flag for each side: True if it plays random moves, False if it is played manually
+}
 
-    fn switch_player(&mut self) -> usize {
-        // Switch current player
-        if self.curr_player == 0 { 1 } else { 0 }
+impl Default for UltimateGame {
+    fn default() -> UltimateGame {
+        UltimateGame::new()
     }
+}
 
-    fn auto_move(&mut self) -> usize {
-        // Automated Move: Return the location (index) for a random, legal move
-        let max_rng = SIZE * SIZE;
-        let mut rng = thread_rng();
-        let mut loc = rng.gen_range(0, max_rng);
-        let mut valid: bool = self.coordinates[loc].legal;
+#[wasm_bindgen]
+impl UltimateGame {
+    pub fn new() -> Self {
+        // Initializes the nine small boards and the meta-board, with random players on both sides
+        utils::set_panic_hook();
+        UltimateGame { engine: Ultimate::new(), auto: [true, true] }
+    }
 
-        // Make sure the move is valid
-        while valid == false {
-            loc = rng.gen_range(0, max_rng);
-            valid = self.coordinates[loc].legal;
-        }
-        loc
+    pub fn get_seed(&self) -> u64 {
+        // Returns the seed behind every random move, so a game can be replayed
+        self.engine.seed()
     }
 
-    fn manual_move(&mut self, selected_move: usize) -> usize {
-        // Manual Move: Ask the user for the location where they want to place their piece
+    pub fn set_seed(&mut self, seed: u64) {
+        // Restarts random play from `seed` (a javascript `BigInt`)
+        self.engine.set_seed(seed);
+    }
 
-        // Make sure the move is valid
-        match &selected_move {
-            0 ... 8 => selected_move,
-            _ => 9,
-        }
+    pub fn start(&mut self, p1_auto: bool, p2_auto: bool) {
+        // Set each side to random (true) or manual (false) play in order to start the game
+        self.auto = [p1_auto, p2_auto];
     }
 
-    fn is_endgame(&mut self) -> bool {
-        // Checks for end game win/draw states returning True if an endgame is reached, False otherwise
-        let total_states = SIZE + SIZE + 2;
-        let slices = [[0, 1, 2], [3, 4, 5], [6, 7, 8],
-                      [0, 3, 6], [1, 4, 7], [2, 5, 8],
-                      [0, 4, 8], [2, 4, 6]];
-        let mut board_slice: Vec<char> = vec![];
-
-        // Pass list of arrays as rows and loop over them to check for win state
-        for slice in 0..total_states {
-            // e.g. [0, 1, 2]
-            let state = &slices[slice];
-
-            for index in 0..SIZE {
-                // e.g. 0
-                let loc = state[index];
-                let x = self.coordinates[loc].x;
-                let y = self.coordinates[loc].y;
-                board_slice.push(self.board[x][y])
-            }
+    pub fn render_players(&self) -> String {
+        // Render players and their playing type (random/manual)
+        let name = |auto: bool| if auto { "random" } else { "manual" };
+        format!("Player 1 :: {} ({} play)\nPlayer 2 :: {} ({} play)", P1, name(self.auto[0]), P2, name(self.auto[1]))
+    }
 
-            // Check the current slice of the board for a winning state
-            if self.is_win(&board_slice) == true {
-                board_slice.clear();
-                return true;
-            }
-            board_slice.clear();
+    pub fn render_board(&self) -> String {
+        // Render the whole grid as a string (for WASM), with double lines between small boards
+        // and every square of a claimed small board showing its winner's piece
+        let labels: Vec<String> = (0..SQUARES)
+            .map(|loc| match self.engine.board_winner(loc / BOARDS) {
+                Some(player) => format!(" {} ", self.engine.players[player]),
+                None => format!(" {} ", self.engine.piece_at(loc)),
+            })
+            .collect();
+        let small_divider = vec![["───"; SIZE].join("┼"); SIZE].join("╫");
+        let meta_divider = vec![["═══"; SIZE].join("╪"); SIZE].join("╬");
+        render_grid(&labels, &small_divider, &meta_divider)
+    }
+
+    pub fn render_indexed_board(&self) -> String {
+        // Render indexed grid as a string (for WASM) to allow for keystrokes, showing only the indices
+        // that can be played this turn, so the overlay also shows which small board is open
+        let cell_width = index_width(SQUARES);
+        let labels: Vec<String> = (0..SQUARES)
+            .map(|loc| match self.engine.is_legal(loc) {
+                true => format!("{:>cell$} ", loc, cell = cell_width),
+                false => " ".repeat(cell_width + 1),
+            })
+            .collect();
+        let divider = " ".repeat((cell_width + 2) * SIDE - 1);
+        render_grid(&labels, &divider, &divider)
+    }
+
+    pub fn update(&mut self, manual_move: usize) -> Result<usize, JsError> {
+        // Have the current player move: a random move for an automatic side, otherwise `manual_move`,
+        // throwing a javascript `Error` if the engine rejects it or the game is already over
+        let loc = match self.auto[self.engine.curr_player] {
+            true => self.engine.random_move().ok_or(engine::MoveError::GameOver)?,
+            false => manual_move,
+        };
+        self.engine.place(loc)?;
+        Ok(loc)
+    }
+
+    pub fn get_next_board(&self) -> Option<usize> {
+        // Returns the small board the current player must play on, or `undefined` if any open board will do
+        self.engine.next_board
+    }
+
+    pub fn get_history(&self) -> Vec<usize> {
+        // Returns the locations (indices) played so far, in order
+        self.engine.history.iter().map(|played| played.loc).collect()
+    }
+
+    pub fn legal_moves(&self) -> Vec<usize> {
+        // Returns the locations (indices) that can be played this turn
+        self.engine.legal_moves()
+    }
+
+    pub fn is_legal(&self, loc: usize) -> bool {
+        // Returns True if the location (index) can be played by the current player
+        self.engine.is_legal(loc)
+    }
+
+    pub fn get_end_game(&self) -> bool {
+        // Returns end_game boolean
+        self.engine.end_game
+    }
+
+    pub fn get_outcome(&self) -> GameOutcome {
+        // Returns whether the game is in progress, won (with the winner and the meta-board line) or drawn
+        GameOutcome::from(&self.engine.outcome)
+    }
+
+    pub fn declare_winner(&self) -> String {
+        // Winner message
+        match self.engine.winner() {
+            Some(player) => format!("Player {} is the WINNER of the meta-board!", self.engine.players[player]),
+            None => "DRAW: nobody wins".to_string(),
         }
+    }
 
-        // if the board is full, check for drawn state
-        self.is_draw()
+    pub fn reset(&mut self) {
+        // Reset Game
+        self.engine.reset();
     }
+}
 
-    fn is_draw(&mut self) -> bool {
-        // Checks for drawn states and returns True if a drawn state is reached, False otherwise
-        let mut is_drawn = true;
-        for row in self.board.iter() {
-            is_drawn = match row {
-                [' ', _, _] | [_, ' ', _] | [_, _, ' '] => false,  // board is not full
-                _ => true,                                         // board is full
-            };
-            if !is_drawn {
-                return false;
-            }
+#[wasm_bindgen]
+pub struct NotaktoGame {
+    // Browser-facing wrapper around the engine's notakto, where both players place X on one or more 3x3 boards
+    engine: Notakto,                    // boards, dead boards and endgame state
+    players: [Option<Difficulty>; 2],   // difficulty of each automatic side, None if it is played manually
+}
+
+#[wasm_bindgen]
+impl NotaktoGame {
+    pub fn new(boards: usize) -> Result<NotaktoGame, JsError> {
+        // Initializes `boards` empty 3x3 boards, with perfect automatic players on both sides,
+        // throwing a javascript `Error` if there are no boards to play on
+        utils::set_panic_hook();
+        if boards == 0 {
+            return Err(JsError::new("notakto needs at least one board"));
         }
-        is_drawn
+        Ok(NotaktoGame { engine: Notakto::new(boards), players: [Some(Difficulty::Perfect); 2] })
+    }
+
+    pub fn get_seed(&self) -> u64 {
+        // Returns the seed behind every random move, so a game can be replayed
+        self.engine.seed()
+    }
+
+    pub fn set_seed(&mut self, seed: u64) {
+        // Restarts random play from `seed` (a javascript `BigInt`)
+        self.engine.set_seed(seed);
+    }
+
+    pub fn start(&mut self, p1: Option<Difficulty>, p2: Option<Difficulty>) {
+        // Set the automatic/manual play settings for each player in order to start the game:
+        // `undefined` for manual play, otherwise the difficulty of an automatic player
+        self.players = [p1, p2];
     }
 
-    fn is_win(&mut self, row: &Vec<char>) -> bool {
-        // Checks for win states and returns True if a win state is reached, False otherwise
-        if row == &self.win_states.p1_win_state {
-            self.winner = 0;
-            return true;
+    pub fn get_boards(&self) -> usize {
+        // Returns the number of boards in the game
+        self.engine.boards.len()
+    }
+
+    pub fn is_dead(&self, board: usize) -> bool {
+        // Returns True if the board has three in a row and takes no more moves
+        self.engine.is_dead(board)
+    }
+
+    pub fn render_players(&self) -> String {
+        // Render players and their playing type (e.g. perfect/manual)
+        let name = |difficulty: Option<Difficulty>| match difficulty {
+            Some(difficulty) => engine::Difficulty::from(difficulty).to_string(),
+            None => "manual".to_string(),
+        };
+        format!("Player 1 :: {} play\nPlayer 2 :: {} play", name(self.players[0]), name(self.players[1]))
+    }
+
+    pub fn render_board(&self) -> String {
+        // Render the boards side by side as a string (for WASM), with double lines between boards
+        // and a `†` under each dead board
+        let labels: Vec<String> = (0..self.engine.total_squares())
+            .map(|loc| format!(" {} ", self.engine.piece_at(loc)))
+            .collect();
+        let divider = vec![["───"; SIZE].join("┼"); self.get_boards()].join("╫");
+        let marks: Vec<String> = (0..self.get_boards())
+            .map(|board| format!("{:^width$}", if self.is_dead(board) { "†" } else { "" }, width = 4 * SIZE - 1))
+            .collect();
+        render_boards(&labels, &divider) + &marks.join(" ") + "\n"
+    }
+
+    pub fn render_indexed_board(&self) -> String {
+        // Render indexed boards as a string (for WASM) to allow for keystrokes, showing only the indices
+        // that can still be played, so dead boards are blank
+        let cell_width = index_width(self.engine.total_squares());
+        let labels: Vec<String> = (0..self.engine.total_squares())
+            .map(|loc| match self.engine.is_legal(loc) {
+                true => format!("{:>cell$} ", loc, cell = cell_width),
+                false => " ".repeat(cell_width + 1),
+            })
+            .collect();
+        let divider = " ".repeat((cell_width + 2) * SIZE * self.get_boards() - 1);
+        render_boards(&labels, &divider)
+    }
+
+    pub fn update(&mut self, manual_move: usize) -> Result<usize, JsError> {
+        // Have the current player move: the automatic side's choice at its difficulty, otherwise `manual_move`,
+        // throwing a javascript `Error` if the engine rejects it
+        let loc = match self.players[self.engine.curr_player] {
+            Some(difficulty) => self.engine.choose_move(difficulty.into()).unwrap_or(manual_move),
+            None => manual_move,
+        };
+        self.engine.place(loc)?;
+        Ok(loc)
+    }
+
+    pub fn get_value(&self) -> String {
+        // Names the game's element of the misère quotient, e.g. "c²"
+        self.engine.value().to_string()
+    }
+
+    pub fn get_winning_moves(&self) -> Vec<usize> {
+        // Returns the locations (indices) that win against any reply, as a hint; empty if every move loses
+        self.engine.winning_moves()
+    }
+
+    pub fn get_history(&self) -> Vec<usize> {
+        // Returns the locations (indices) played so far, in order
+        self.engine.history.iter().map(|played| played.loc).collect()
+    }
+
+    pub fn legal_moves(&self) -> Vec<usize> {
+        // Returns the locations (indices) that can still be played
+        self.engine.legal_moves()
+    }
+
+    pub fn is_legal(&self, loc: usize) -> bool {
+        // Returns True if the location (index) can be played by the current player
+        self.engine.is_legal(loc)
+    }
+
+    pub fn get_end_game(&self) -> bool {
+        // Returns end_game boolean
+        self.engine.end_game
+    }
+
+    pub fn get_outcome(&self) -> GameOutcome {
+        // Returns whether the game is in progress or won, with the winner and the line that killed the last board
+        GameOutcome::from(&self.engine.outcome)
+    }
+
+    pub fn declare_winner(&self) -> String {
+        // Winner message
+        match self.engine.winner() {
+            Some(player) => format!("Player {} is the WINNER! Player {} killed the last board.", player + 1, 2 - player),
+            None => "The game is still in play".to_string(),
         }
-        if row == &self.win_states.p2_win_state {
-            self.winner = 1;
-            return true;
+    }
+
+    pub fn reset(&mut self) {
+        // Reset Game
+        self.engine.reset();
+    }
+}
+
+fn render_boards(labels: &[String], divider: &str) -> String {
+    // Lays out one label per location (index) as 3x3 boards side by side, with `divider` between rows
+    let boards = labels.len() / notakto::SQUARES;
+    let mut board_state: String = "".to_string();
+    for row in 0..SIZE {
+        let rows: Vec<String> = (0..boards)
+            .map(|board| {
+                let squares: Vec<&str> = (0..SIZE)
+                    .map(|col| labels[board * notakto::SQUARES + row * SIZE + col].as_str())
+                    .collect();
+                squares.join("│")
+            })
+            .collect();
+        board_state += &format!("\n{}\n", rows.join("║"));
+        if row + 1 < SIZE {
+            board_state += &format!("{}\n", divider);
         }
-        false
     }
+    board_state += "\n";
+    board_state
 }
 
-fn coord_mapping() -> Vec<Coord> {
-    // Generates a coordinate mapping of vector indices -> coordinates on the board
-    // and `legal` represents whether a square is available for placing a piece (True)
-    // or already has a piece placed on it (False)
-    let mut coordinates: Vec<Coord> = vec![];
-    for i in 0..SIZE {
-        for j in 0..SIZE {
-            let coord = Coord { x: i, y: j, legal: true };
-            coordinates.push(coord);
+fn render_grid(labels: &[String], small_divider: &str, meta_divider: &str) -> String {
+    // Lays out one label per location (index) as the 9x9 grid of ultimate tic-tac-toe, with
+    // `small_divider` between the rows of a small board and `meta_divider` between rows of small boards
+    let mut board_state: String = "".to_string();
+    for row in 0..SIDE {
+        let boards: Vec<String> = (0..SIZE)
+            .map(|board_col| {
+                let squares: Vec<&str> = (0..SIZE)
+                    .map(|col| labels[Ultimate::grid_location(row, board_col * SIZE + col)].as_str())
+                    .collect();
+                squares.join("│")
+            })
+            .collect();
+        board_state += &format!("\n{}\n", boards.join("║"));
+        if row + 1 < SIDE {
+            board_state += &format!("{}\n", if (row + 1) % SIZE == 0 { meta_divider } else { small_divider });
         }
     }
-    coordinates
+    board_state += "\n";
+    board_state
 }
@@ -1,16 +1,131 @@
 //! Test suite for the Web and headless browsers.
 
-// source: sample test grabbed from https://rustwasm.github.io/wasm-pack/book/print.html
-// as reference. These are to be replaced by custom tests relating to this specific project 
+// Checks the exports the page calls, in particular that engine errors reach javascript as a thrown `Error`
+// rather than a panic. Run with `wasm-pack test --headless --firefox`.
 
 #![cfg(target_arch = "wasm32")]
 
 extern crate wasm_bindgen_test;
 use wasm_bindgen_test::*;
+use wasm_tic_tac_toe::{Difficulty, Game, NotaktoGame, Rules, Strategy, UltimateGame, Value};
 
 wasm_bindgen_test_configure!(run_in_browser);
 
+fn manual_game() -> Game {
+    // A standard 3x3 game with both sides played manually
+    let mut game = Game::new();
+    assert!(game.start(None, None, Strategy::AlphaBeta, Rules::Standard).is_ok());
+    game
+}
+
+#[wasm_bindgen_test]
+fn constructors_reject_bad_boards() {
+    // Boards the engine cannot play throw instead of panicking
+    assert!(Game::with_size(7, 6, 4).is_ok());
+    assert!(Game::with_size(3, 3, 4).is_err());
+    assert!(Game::with_layers(3, 3, 0, 3).is_err());
+    assert!(Game::with_layers(16, 16, 2, 5).is_err());
+    assert!(NotaktoGame::new(2).is_ok());
+    assert!(NotaktoGame::new(0).is_err());
+}
+
+#[wasm_bindgen_test]
+fn start_rejects_players_that_cannot_search() {
+    // Automatic players that cannot search the board, or any search in a wild game, throw from `start`
+    let mut game = Game::with_size(4, 4, 4).ok().unwrap();
+    assert!(game.start(Some(Difficulty::Perfect), None, Strategy::Minimax, Rules::Standard).is_err());
+    assert!(game.start(Some(Difficulty::Perfect), None, Strategy::AlphaBeta, Rules::Standard).is_ok());
+    let mut wild = Game::new();
+    wild.set_wild(true);
+    assert!(wild.start(Some(Difficulty::Easy), None, Strategy::AlphaBeta, Rules::Standard).is_err());
+    assert!(wild.start(Some(Difficulty::Random), None, Strategy::AlphaBeta, Rules::Standard).is_ok());
+}
+
+#[wasm_bindgen_test]
+fn update_rejects_illegal_moves() {
+    // Occupied squares, squares off the board and moves after the game ends throw
+    let mut game = manual_game();
+    assert_eq!(game.update(4).ok(), Some(4));
+    assert!(game.update(4).is_err());
+    assert!(game.update(9).is_err());
+    for &loc in &[0, 3, 1, 5, 2] {
+        assert!(game.update(loc).is_ok());
+    }
+    assert!(game.get_end_game());
+    assert!(game.update(8).is_err());
+}
+
+#[wasm_bindgen_test]
+fn update_with_piece_places_either_piece_in_wild_games() {
+    // Wild games take either piece, other games only the mover's own
+    let mut game = manual_game();
+    assert!(game.update_with_piece(0, 1).is_err());
+    game.set_wild(true);
+    assert!(game.start(None, None, Strategy::AlphaBeta, Rules::Standard).is_ok());
+    assert_eq!(game.update_with_piece(0, 1).ok(), Some(0));
+    assert_eq!(game.get_history(), vec![0]);
+    assert!(game.render_board().contains('O'));
+}
+
+#[wasm_bindgen_test]
+fn undo_and_redo_whole_turns() {
+    // Undo takes back moves until the same player is to move again, and redo replays them
+    let mut game = manual_game();
+    assert!(game.update(4).is_ok());
+    assert!(game.update(0).is_ok());
+    assert_eq!(game.undo(), 2);
+    assert!(game.get_history().is_empty());
+    assert_eq!(game.redo(), 2);
+    assert_eq!(game.get_history(), vec![4, 0]);
+}
+
+#[wasm_bindgen_test]
+fn evaluations_cover_open_squares() {
+    // Every open square of the empty board draws, and wild games have no hints
+    let game = manual_game();
+    let evaluations = game.get_evaluations().ok().unwrap();
+    assert_eq!(evaluations.len(), 9);
+    assert!(evaluations.iter().all(|evaluation| evaluation.value() == Some(Value::Draw)));
+    let mut wild = Game::new();
+    wild.set_wild(true);
+    assert!(wild.get_evaluations().is_err());
+}
+
+#[wasm_bindgen_test]
+fn mcts_settings_tune_players() {
+    // The exploration constant is checked, and tuned MCTS players still start and move
+    let mut game = Game::new();
+    assert!(game.set_mcts_exploration(Some(-1.0)).is_err());
+    assert!(game.set_mcts_exploration(Some(1.0)).is_ok());
+    game.set_mcts_playouts(Some(50));
+    game.set_mcts_time(None);
+    assert!(game.start(Some(Difficulty::Hard), None, Strategy::Mcts, Rules::Standard).is_ok());
+    let loc = game.update(0).ok().unwrap();
+    assert_eq!(game.get_history(), vec![loc]);
+}
+
+#[wasm_bindgen_test]
+fn ultimate_game_plays_to_the_end() {
+    // Two automatic sides finish the game, after which `update` throws
+    let mut game = UltimateGame::new();
+    game.set_seed(7);
+    game.start(true, true);
+    while !game.get_end_game() {
+        assert!(game.update(0).is_ok());
+    }
+    assert!(game.update(0).is_err());
+    assert!(!game.get_history().is_empty());
+}
+
 #[wasm_bindgen_test]
-fn pass() {
-    assert_eq!(1 + 1, 2);
+fn notakto_game_plays_to_the_end() {
+    // Perfect play wins one board for the first player, after which `update` throws
+    let mut game = NotaktoGame::new(1).ok().unwrap();
+    game.set_seed(7);
+    game.start(Some(Difficulty::Perfect), Some(Difficulty::Random));
+    while !game.get_end_game() {
+        assert!(game.update(0).is_ok());
+    }
+    assert_eq!(game.get_outcome().player(), Some(0));
+    assert!(game.update(0).is_err());
 }